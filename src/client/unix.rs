@@ -0,0 +1,314 @@
+use super::{Timeouts, read_limited, too_large};
+use crate::MAX_BODY_SIZE;
+use crate::xmlfmt::{XmlError, XmlResult};
+use flate2::read::GzDecoder;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/*
+    HTTP over a Unix domain socket, for servers listening on a socket file
+    the way Supervisor does with `unix:///var/run/supervisor.sock`.
+    The whole path of the URI is the socket, and calls are posted to /RPC2,
+    as Supervisor's own client does. One request is sent per connection.
+    Responses are asked for gzipped, as the agent does over TCP.
+    The timeouts of the client apply as they do over TCP: the connect timeout to connecting,
+    the read timeout to each read, and the overall timeout to the whole call.
+    Response bodies larger than `MAX_BODY_SIZE` are refused rather than read into memory.
+*/
+
+const SCHEME: &str = "unix://";
+const RPC_PATH: &str = "/RPC2";
+
+// Socket path of a `unix://` URI, `None` for any other URI.
+pub(super) fn socket_path(uri: &str) -> Option<&str> {
+    uri.strip_prefix(SCHEME)
+}
+
+/// Post `body` with `headers` to the server listening on `socket`,
+/// and return the body of its response.
+pub(super) fn post(
+    socket: &str,
+    body: &[u8],
+    headers: &[(&str, &str)],
+    timeouts: &Timeouts,
+) -> XmlResult<Vec<u8>> {
+    let head = request_head(body, headers)?;
+    let http_error = |e: io::Error| XmlError::Http(format!("{}: {}", socket, e));
+    let deadline = timeouts.global.map(|timeout| Instant::now() + timeout);
+    let connect_timeout = shortest(timeouts.connect, remaining(deadline).map_err(http_error)?);
+    let mut stream = connect(socket, connect_timeout).map_err(http_error)?;
+    stream
+        .set_write_timeout(remaining(deadline).map_err(http_error)?)
+        .map_err(http_error)?;
+    stream.write_all(head.as_bytes()).map_err(http_error)?;
+    stream.write_all(body).map_err(http_error)?;
+    let stream = TimedStream {
+        stream,
+        read_timeout: timeouts.read,
+        deadline,
+    };
+    let (status, content) = read_response(BufReader::new(stream)).map_err(http_error)?;
+    match status {
+        200..=299 => Ok(content),
+        _ => Err(XmlError::Http(format!("http status: {}", status))),
+    }
+}
+
+// Headers are written as given, so a name or value breaking the head is refused,
+// rather than letting it end a header early or smuggle in others.
+fn request_head(body: &[u8], headers: &[(&str, &str)]) -> XmlResult<String> {
+    let mut head = format!("POST {} HTTP/1.1\r\nHost: localhost\r\n", RPC_PATH);
+    for &(name, value) in headers {
+        if name.is_empty() || !name.bytes().all(is_token) {
+            return Err(XmlError::Http(format!("invalid header name {:?}", name)));
+        }
+        if value.bytes().any(|b| matches!(b, b'\r' | b'\n' | b'\0')) {
+            return Err(XmlError::Http(format!("invalid value for header {}", name)));
+        }
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!(
+        "Content-Type: text/xml\r\nContent-Length: {}\r\nAccept-Encoding: gzip\r\n\
+         Connection: close\r\n\r\n",
+        body.len()
+    ));
+    Ok(head)
+}
+
+// Characters allowed in a header name, see RFC 9110, section 5.6.2.
+fn is_token(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+// The standard library cannot bound the connection to a Unix socket, which blocks while
+// the backlog of the server is full: it is made on its own thread, given up after `timeout`.
+fn connect(socket: &str, timeout: Option<Duration>) -> io::Result<UnixStream> {
+    let Some(timeout) = timeout else {
+        return UnixStream::connect(socket);
+    };
+    let (sender, receiver) = mpsc::channel();
+    let path = socket.to_owned();
+    thread::spawn(move || sender.send(UnixStream::connect(path)));
+    receiver
+        .recv_timeout(timeout)
+        .unwrap_or_else(|_| Err(io::ErrorKind::TimedOut.into()))
+}
+
+// Time left before `deadline`, failing once it has passed. `None` without a deadline.
+fn remaining(deadline: Option<Instant>) -> io::Result<Option<Duration>> {
+    match deadline.map(|deadline| deadline.saturating_duration_since(Instant::now())) {
+        Some(left) if left.is_zero() => Err(io::ErrorKind::TimedOut.into()),
+        left => Ok(left),
+    }
+}
+
+fn shortest(a: Option<Duration>, b: Option<Duration>) -> Option<Duration> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+// A connection whose reads wait no longer than the read timeout, nor past the deadline.
+struct TimedStream {
+    stream: UnixStream,
+    read_timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl Read for TimedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let timeout = shortest(self.read_timeout, remaining(self.deadline)?);
+        self.stream.set_read_timeout(timeout)?;
+        self.stream.read(buf)
+    }
+}
+
+// Status code and body of an HTTP/1.1 response, sized by Content-Length, chunked, or up to EOF,
+// and decompressed when gzipped.
+fn read_response<R: BufRead>(mut reader: R) -> io::Result<(u16, Vec<u8>)> {
+    let status_line = read_line(&mut reader)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| invalid(format!("malformed status line {:?}", status_line)))?;
+
+    let mut length = None;
+    let mut chunked = false;
+    let mut gzipped = false;
+    loop {
+        let line = read_line(&mut reader)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| invalid(format!("malformed header {:?}", line)))?;
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("Content-Length") {
+            let len = value.parse::<u64>().map_err(|e| invalid(e.to_string()))?;
+            length = Some(len);
+        } else if name.eq_ignore_ascii_case("Transfer-Encoding") {
+            chunked = value.to_ascii_lowercase().contains("chunked");
+        } else if name.eq_ignore_ascii_case("Content-Encoding") {
+            gzipped = value.eq_ignore_ascii_case("gzip") || value.eq_ignore_ascii_case("x-gzip");
+        }
+    }
+
+    let mut body = Vec::new();
+    match (chunked, length) {
+        (true, _) => read_chunks(&mut reader, &mut body)?,
+        (false, Some(len)) => read_sized(&mut reader, len, &mut body)?,
+        (false, None) => read_limited(&mut reader, &mut body)?,
+    }
+    if gzipped {
+        let mut decoded = Vec::new();
+        read_limited(GzDecoder::new(body.as_slice()), &mut decoded)?;
+        body = decoded;
+    }
+    Ok((status, body))
+}
+
+fn read_chunks<R: BufRead>(reader: &mut R, body: &mut Vec<u8>) -> io::Result<()> {
+    loop {
+        let line = read_line(reader)?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = u64::from_str_radix(size, 16)
+            .map_err(|_| invalid(format!("malformed chunk size {:?}", line)))?;
+        if size == 0 {
+            // Skip the trailers, up to the empty line ending the response.
+            while !read_line(reader)?.is_empty() {}
+            return Ok(());
+        }
+        read_sized(&mut *reader, size, body)?;
+        read_line(reader)?;
+    }
+}
+
+// Append `len` more bytes to `body`, which may not grow past `MAX_BODY_SIZE`.
+// They are read as they come, not allocated upfront on the word of the peer.
+fn read_sized<R: Read>(reader: R, len: u64, body: &mut Vec<u8>) -> io::Result<()> {
+    if body.len() as u64 + len > MAX_BODY_SIZE {
+        return Err(too_large());
+    }
+    if reader.take(len).read_to_end(body)? as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+// One line, without its line ending. Fails at EOF, where a line was still expected.
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_socket_path_of_unix_uri() {
+        assert_eq!(
+            socket_path("unix:///var/run/supervisor.sock"),
+            Some("/var/run/supervisor.sock")
+        );
+        assert_eq!(socket_path("http://localhost:9001/RPC2"), None);
+    }
+
+    #[test]
+    fn refuses_headers_breaking_the_head() {
+        let head = request_head(b"<x/>", &[("X-Token", "a b\tc")]).unwrap();
+        assert!(head.contains("\r\nX-Token: a b\tc\r\n"));
+        assert!(
+            head.ends_with(
+                "Content-Length: 4\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n"
+            )
+        );
+        let invalid = [
+            ("", "value"),
+            ("X Token", "value"),
+            ("X-Token:", "value"),
+            ("X-Token\r\nHost", "value"),
+            ("X-Token", "value\r\nHost: elsewhere"),
+            ("X-Token", "value\n"),
+            ("X-Token", "val\0ue"),
+        ];
+        for (name, value) in invalid {
+            match request_head(b"", &[(name, value)]) {
+                Err(XmlError::Http(_)) => {}
+                other => panic!("{:?}: {:?} should be refused, got {:?}", name, value, other),
+            }
+        }
+        // Nothing is sent for a refused header.
+        let error = post(
+            "/nonexistent/socket",
+            b"",
+            &[("X-Token", "a\nb")],
+            &Timeouts::default(),
+        );
+        assert!(!error.unwrap_err().to_string().contains("nonexistent"));
+    }
+
+    #[test]
+    fn reads_sized_response() {
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello, and more";
+        let (status, body) = read_response(response.as_bytes()).unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, b"hello");
+    }
+
+    #[test]
+    fn reads_chunked_response() {
+        let response = concat!(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+            "5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\nTrailer: x\r\n\r\n",
+        );
+        let (_, body) = read_response(response.as_bytes()).unwrap();
+        assert_eq!(body, b"hello, world");
+    }
+
+    #[test]
+    fn reads_response_up_to_eof() {
+        let response = "HTTP/1.0 404 Not Found\r\nServer: test\r\n\r\nmissing";
+        let (status, body) = read_response(response.as_bytes()).unwrap();
+        assert_eq!(status, 404);
+        assert_eq!(body, b"missing");
+        assert!(read_response("HTTP/1.1 OK\r\n\r\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn refuses_oversized_responses() {
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 99999999999\r\n\r\nhello";
+        assert!(read_response(response.as_bytes()).is_err());
+        let response = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nffffffffff\r\n";
+        assert!(read_response(response.as_bytes()).is_err());
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nhello";
+        let error = read_response(response.as_bytes()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn reads_gzipped_response() {
+        let body = crate::client::gzip(b"hello").unwrap();
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend(body);
+        let (_, body) = read_response(response.as_slice()).unwrap();
+        assert_eq!(body, b"hello");
+    }
+}
@@ -1,12 +1,17 @@
 #[macro_use]
 extern crate serde;
-extern crate serde_xml_rs;
 pub extern crate ureq;
 
 pub mod client;
 pub mod server;
 pub mod xmlfmt;
 
-pub use client::{Client, call, call_value};
+// Largest body read once decompressed, as in Python's `xmlrpc` modules:
+// calls compressed on the server, and every response over a Unix socket on the client.
+const MAX_BODY_SIZE: u64 = 20 * 1024 * 1024;
+
+pub use client::{Client, ClientBuilder, call, call_value};
 pub use server::Server;
-pub use xmlfmt::{MethodResponse, Params, Value, XmlError, XmlResponse};
+pub use xmlfmt::{
+    Fault, FaultCode, MethodResponse, Params, Value, XmlDateTime, XmlError, XmlResponse,
+};
@@ -0,0 +1,91 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+/// Credentials presented by a caller in the `Authorization` header of its request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// `Authorization: Basic`, a user name and its password.
+    Basic { user: String, password: String },
+    /// `Authorization: Bearer`, an opaque token.
+    Bearer(String),
+}
+
+impl Credentials {
+    /// Read the value of an `Authorization` header. `None` for other schemes and malformed values.
+    pub fn parse(authorization: &str) -> Option<Credentials> {
+        let (scheme, value) = authorization.trim().split_once(' ')?;
+        let value = value.trim();
+        if scheme.eq_ignore_ascii_case("Basic") {
+            let decoded = String::from_utf8(STANDARD.decode(value).ok()?).ok()?;
+            let (user, password) = decoded.split_once(':')?;
+            Some(Credentials::Basic {
+                user: user.to_owned(),
+                password: password.to_owned(),
+            })
+        } else if scheme.eq_ignore_ascii_case("Bearer") && !value.is_empty() {
+            Some(Credentials::Bearer(value.to_owned()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Checks who calls the server, before the call is read. See `Server::set_authenticator`.
+///
+/// Requests without credentials, or with credentials rejected by `authenticate`,
+/// are answered with `401 Unauthorized` and a `WWW-Authenticate: {challenge}` header.
+/// Any `Fn(&Credentials) -> Option<String>` is an authenticator asking for Basic credentials:
+/// ```no_run
+/// # use xml_rpc::Server;
+/// # use xml_rpc::server::Credentials;
+/// let mut server = Server::new(8000).unwrap();
+/// server.set_authenticator(|credentials: &Credentials| match credentials {
+///     Credentials::Basic { user, password } if user == "admin" && password == "secret" => {
+///         Some(user.clone())
+///     }
+///     _ => None,
+/// });
+/// ```
+pub trait Authenticator: Send + Sync {
+    /// Identity of the caller presenting `credentials`, `None` to reject them.
+    /// It is given to handlers as `CallContext::principal`.
+    fn authenticate(&self, credentials: &Credentials) -> Option<String>;
+
+    /// Value of the `WWW-Authenticate` header sent to rejected callers.
+    fn challenge(&self) -> String {
+        r#"Basic realm="xml-rpc""#.to_owned()
+    }
+}
+
+impl<F> Authenticator for F
+where
+    F: Fn(&Credentials) -> Option<String> + Send + Sync,
+{
+    fn authenticate(&self, credentials: &Credentials) -> Option<String> {
+        self(credentials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_authorization_header() {
+        assert_eq!(
+            Credentials::parse("Basic YWRtaW46czNjcjp0"),
+            Some(Credentials::Basic {
+                user: "admin".to_owned(),
+                password: "s3cr:t".to_owned()
+            })
+        );
+        assert_eq!(
+            Credentials::parse("bearer abc.def"),
+            Some(Credentials::Bearer("abc.def".to_owned()))
+        );
+        assert_eq!(Credentials::parse("Basic !!!"), None);
+        assert_eq!(Credentials::parse("Basic YWRtaW4="), None);
+        assert_eq!(Credentials::parse("Digest username=admin"), None);
+        assert_eq!(Credentials::parse("Bearer"), None);
+    }
+}
@@ -0,0 +1,113 @@
+use super::HeadersIter;
+#[cfg(feature = "tls")]
+use super::tls::Peer;
+use std::net::SocketAddr;
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+use tiny_http::Request;
+
+/// What a handler registered with `register_with_context` knows about the call it answers.
+#[derive(Debug, Clone, Default)]
+pub struct CallContext {
+    method_name: String,
+    path: String,
+    mount: Option<String>,
+    remote_addr: Option<SocketAddr>,
+    headers: Vec<(String, String)>,
+    principal: Option<String>,
+    #[cfg(feature = "tls")]
+    peer_certificate: Option<Arc<[u8]>>,
+}
+
+impl CallContext {
+    pub(super) fn from_request(
+        request: &Request,
+        method_name: &str,
+        mount: Option<String>,
+        principal: Option<String>,
+    ) -> Self {
+        let path = request.url().split('?').next().unwrap_or_default();
+        let headers = request
+            .headers()
+            .iter()
+            .map(|h| (h.field.as_str().to_string(), h.value.to_string()))
+            .collect();
+        Self {
+            method_name: method_name.to_owned(),
+            path: path.to_owned(),
+            mount,
+            remote_addr: request.remote_addr().copied(),
+            headers,
+            principal,
+            #[cfg(feature = "tls")]
+            peer_certificate: None,
+        }
+    }
+
+    // The call context of a request relayed by the HTTPS front, for the client it stands for.
+    #[cfg(feature = "tls")]
+    pub(super) fn with_peer(self, peer: &Peer) -> Self {
+        Self {
+            remote_addr: Some(peer.addr),
+            peer_certificate: peer.certificate.clone(),
+            ..self
+        }
+    }
+
+    // The same call context, for one of the calls of a `system.multicall`.
+    pub(super) fn with_method_name(&self, method_name: &str) -> Self {
+        Self {
+            method_name: method_name.to_owned(),
+            ..self.clone()
+        }
+    }
+
+    /// Name of the method called, the one of the current call inside a `system.multicall`.
+    pub fn method_name(&self) -> &str {
+        &self.method_name
+    }
+
+    /// Path the request was posted to, without its query string.
+    /// It is the whole path, mount included: `/plugins/foo` for a mount at `/plugins`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Path of the mount answering the call, such as `/plugins` for a request to `/plugins/foo`.
+    /// `None` for the methods registered on the server itself.
+    pub fn mount(&self) -> Option<&str> {
+        self.mount.as_deref()
+    }
+
+    /// Address of the caller. `None` when it is not known, such as over a Unix socket.
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    /// Value of a header of the request, looked up regardless of case.
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| &v[..])
+    }
+
+    /// All the headers of the request.
+    pub fn headers(&self) -> HeadersIter<'_> {
+        HeadersIter {
+            iter: self.headers.iter(),
+        }
+    }
+
+    /// Identity the caller authenticated as, `None` when the server has no `Authenticator`.
+    pub fn principal(&self) -> Option<&str> {
+        self.principal.as_deref()
+    }
+
+    /// DER certificate the caller presented over HTTPS, verified against the CAs
+    /// given to `TlsConfig::client_certificates`. `None` when it presented none.
+    #[cfg(feature = "tls")]
+    pub fn peer_certificate(&self) -> Option<&[u8]> {
+        self.peer_certificate.as_deref()
+    }
+}
@@ -0,0 +1,99 @@
+use super::{Task, http};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// How `Server::poll` runs the requests it receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Executor {
+    /// On the thread calling `poll`, one request at a time.
+    #[default]
+    CurrentThread,
+    /// On a new thread for every request.
+    ThreadPerRequest,
+    /// On `workers` threads started up front. At most `queue` requests wait for a free worker,
+    /// further ones are answered with `503 Service Unavailable`.
+    Pool { workers: usize, queue: usize },
+}
+
+pub(super) enum Runner {
+    CurrentThread,
+    ThreadPerRequest,
+    Pool(WorkerPool),
+}
+
+impl Runner {
+    pub(super) fn new(executor: Executor) -> Self {
+        match executor {
+            Executor::CurrentThread => Runner::CurrentThread,
+            Executor::ThreadPerRequest => Runner::ThreadPerRequest,
+            Executor::Pool { workers, queue } => Runner::Pool(WorkerPool::new(workers, queue)),
+        }
+    }
+
+    pub(super) fn run(&self, task: Task) {
+        match self {
+            Runner::CurrentThread => task.run(),
+            Runner::ThreadPerRequest => {
+                thread::spawn(move || task.run());
+            }
+            Runner::Pool(pool) => pool.run(task),
+        }
+    }
+}
+
+pub(super) struct WorkerPool {
+    sender: Option<SyncSender<Task>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    fn new(workers: usize, queue: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<Task>(queue);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || worker(&receiver))
+            })
+            .collect();
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    fn run(&self, task: Task) {
+        let sender = self.sender.as_ref().expect("worker pool is running");
+        match sender.try_send(task) {
+            Ok(()) => {}
+            Err(TrySendError::Full(task)) | Err(TrySendError::Disconnected(task)) => {
+                let _ = task.request.respond(http::error(503, "Server busy"));
+            }
+        }
+    }
+}
+
+// Run tasks until the pool is dropped. The lock is only held while waiting for the next task.
+fn worker(receiver: &Mutex<Receiver<Task>>) {
+    loop {
+        let task = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+        match task {
+            Ok(task) => task.run(),
+            Err(_) => return,
+        }
+    }
+}
+
+// Let the queued requests finish, then stop the workers.
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
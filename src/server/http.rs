@@ -0,0 +1,216 @@
+use crate::MAX_BODY_SIZE;
+use flate2::Compression;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::GzEncoder;
+use std::io::{self, Cursor, Read, Write};
+use tiny_http::{Header, Method, Request, Response};
+
+pub(super) type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+/*
+    Checks made before the body of a request is read as a call:
+    - POST only, others are answered with 405 and an `Allow: POST` header
+    - with a `text/xml` body (or `application/xml`), others get 415
+    - sent as is, or compressed with gzip or deflate, others get 415 too
+    Requests to a path without an endpoint are answered with 404 before these,
+    and requests failing authentication with 401 after them.
+    A request passing them gets a method response, a fault when its body is not a valid call.
+*/
+pub(super) fn check(request: &Request) -> Result<(), HttpResponse> {
+    if *request.method() != Method::Post {
+        return Err(error(405, "Method Not Allowed").with_header(header("Allow", "POST")));
+    }
+    let content_type = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Content-Type"))
+        .map(|h| h.value.as_str());
+    if !content_type.is_some_and(is_xml) {
+        return Err(error(415, "Unsupported Media Type"));
+    }
+    if content_coding(request).is_none() {
+        return Err(error(415, "Unsupported Content-Encoding"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Coding {
+    Identity,
+    Gzip,
+    Deflate,
+}
+
+// Coding of the body of a request, `None` when it is not one the server can decode.
+fn content_coding(request: &Request) -> Option<Coding> {
+    let encoding = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Content-Encoding"))
+        .map(|h| h.value.as_str().trim());
+    match encoding {
+        None => Some(Coding::Identity),
+        Some(e) if e.is_empty() || e.eq_ignore_ascii_case("identity") => Some(Coding::Identity),
+        Some(e) if e.eq_ignore_ascii_case("gzip") || e.eq_ignore_ascii_case("x-gzip") => {
+            Some(Coding::Gzip)
+        }
+        Some(e) if e.eq_ignore_ascii_case("deflate") => Some(Coding::Deflate),
+        Some(_) => None,
+    }
+}
+
+// Body of a request that passed `check`, decompressed as it is read.
+pub(super) fn body(request: &mut Request) -> Box<dyn Read + '_> {
+    let coding = content_coding(request).unwrap_or(Coding::Identity);
+    let reader = request.as_reader();
+    match coding {
+        Coding::Identity => Box::new(Limited::new(reader)),
+        Coding::Gzip => Box::new(Limited::new(GzDecoder::new(reader))),
+        Coding::Deflate => Box::new(Limited::new(ZlibDecoder::new(reader))),
+    }
+}
+
+// Fails a read going past `MAX_BODY_SIZE`, so that neither a huge body nor a small compressed
+// one inflating to gigabytes can make the server hold it all.
+struct Limited<R> {
+    inner: io::Take<R>,
+}
+
+impl<R: Read> Limited<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            inner: reader.take(MAX_BODY_SIZE + 1),
+        }
+    }
+}
+
+impl<R: Read> Read for Limited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if self.inner.limit() == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("body larger than {} bytes", MAX_BODY_SIZE),
+            ));
+        }
+        Ok(read)
+    }
+}
+
+// Whether the Accept-Encoding header of a request lists gzip, without a zero weight.
+pub(super) fn accepts_gzip(request: &Request) -> bool {
+    request
+        .headers()
+        .iter()
+        .filter(|h| h.field.equiv("Accept-Encoding"))
+        .flat_map(|h| h.value.as_str().split(','))
+        .any(is_gzip_accepted)
+}
+
+fn is_gzip_accepted(coding: &str) -> bool {
+    let mut parts = coding.split(';').map(str::trim);
+    let name = parts.next().unwrap_or_default();
+    let weight = parts
+        .find_map(|p| p.strip_prefix("q=").or_else(|| p.strip_prefix("Q=")))
+        .and_then(|q| q.parse::<f32>().ok())
+        .unwrap_or(1.0);
+    (name.eq_ignore_ascii_case("gzip") || name.eq_ignore_ascii_case("x-gzip")) && weight > 0.0
+}
+
+fn is_xml(content_type: &str) -> bool {
+    let media_type = content_type.split(';').next().unwrap_or_default().trim();
+    media_type.eq_ignore_ascii_case("text/xml")
+        || media_type.eq_ignore_ascii_case("application/xml")
+}
+
+// A method response, gzipped when asked to. Never chunked, so that it always comes
+// with its Content-Length.
+pub(super) fn xml(content: String, gzip: bool) -> HttpResponse {
+    let xml = header("Content-Type", "text/xml; charset=utf-8");
+    let response = match gzip.then(|| compress(content.as_bytes())).flatten() {
+        Some(compressed) => Response::from_data(compressed)
+            .with_header(xml)
+            .with_header(header("Content-Encoding", "gzip")),
+        None => Response::from_string(content).with_header(xml),
+    };
+    response.with_chunked_threshold(usize::MAX)
+}
+
+// Writing to memory does not fail, but a response is better sent as is than not at all.
+fn compress(data: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).ok()?;
+    encoder.finish().ok()
+}
+
+pub(super) fn error(status: u16, text: &str) -> HttpResponse {
+    Response::from_string(text).with_status_code(status)
+}
+
+// A challenge that is not a valid header value is left out, the status still tells what is wrong.
+pub(super) fn unauthorized(challenge: &str) -> HttpResponse {
+    let response = error(401, "Unauthorized");
+    match Header::from_bytes("WWW-Authenticate", challenge) {
+        Ok(header) => response.with_header(header),
+        Err(()) => response,
+    }
+}
+
+fn header(field: &str, value: &str) -> Header {
+    Header::from_bytes(field, value).expect("header made of visible ASCII")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_xml_content_types() {
+        assert!(is_xml("text/xml"));
+        assert!(is_xml("Text/XML; charset=utf-8"));
+        assert!(is_xml("application/xml"));
+        assert!(!is_xml("application/json"));
+        assert!(!is_xml(""));
+    }
+
+    #[test]
+    fn reads_accepted_gzip_coding() {
+        assert!(is_gzip_accepted("gzip"));
+        assert!(is_gzip_accepted(" GZIP ;q=0.5"));
+        assert!(is_gzip_accepted("x-gzip"));
+        assert!(!is_gzip_accepted("gzip;q=0"));
+        assert!(!is_gzip_accepted("deflate"));
+        assert!(!is_gzip_accepted("*"));
+    }
+
+    #[test]
+    fn limits_plain_bodies() {
+        let zeros = vec![0; MAX_BODY_SIZE as usize + 1];
+        let mut body = Vec::new();
+        let error = Limited::new(zeros.as_slice())
+            .read_to_end(&mut body)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        body.clear();
+        Limited::new(&zeros[1..]).read_to_end(&mut body).unwrap();
+        assert_eq!(body.len(), MAX_BODY_SIZE as usize);
+    }
+
+    #[test]
+    fn limits_decompressed_bodies() {
+        let zeros = compress(&vec![0; MAX_BODY_SIZE as usize + 1]).unwrap();
+        let mut body = Vec::new();
+        let error = Limited::new(GzDecoder::new(zeros.as_slice()))
+            .read_to_end(&mut body)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let zeros = compress(&vec![0; 1000]).unwrap();
+        body.clear();
+        Limited::new(GzDecoder::new(zeros.as_slice()))
+            .read_to_end(&mut body)
+            .unwrap();
+        assert_eq!(body.len(), 1000);
+    }
+}
@@ -0,0 +1,158 @@
+use super::registry::Registry;
+use crate::xmlfmt::{Fault, FaultCode, Member, Params, Value, XmlResponse, from_params};
+
+/*
+    Introspection methods, as answered by Python's SimpleXMLRPCServer and xmlrpc-c:
+    - system.listMethods() -> array of method names
+    - system.methodHelp(name) -> help string
+    - system.methodSignature(name) -> array of signatures, each an array of type names
+      with the return type first, or the string "undef" when none were given
+    - system.getCapabilities() -> struct of { specUrl, specVersion } keyed by spec name
+*/
+
+/// Help string and signatures of a method, reported through introspection.
+///
+/// Returned by `Server::register`, so the description can follow the registration:
+/// ```no_run
+/// # use xml_rpc::{Fault, Server};
+/// let mut server = Server::new(8000).unwrap();
+/// server
+///     .register_typed("add", |(a, b): (i32, i32)| -> Result<i32, Fault> { Ok(a + b) })
+///     .help("Add two integers.")
+///     .signature(["int", "int", "int"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MethodDoc {
+    help: String,
+    signatures: Vec<Vec<String>>,
+}
+
+impl MethodDoc {
+    pub fn help<T: Into<String>>(&mut self, text: T) -> &mut Self {
+        self.help = text.into();
+        self
+    }
+
+    /// Add a signature: the return type, followed by the type of each param.
+    pub fn signature<I, T>(&mut self, types: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.signatures
+            .push(types.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn builtin(help: &str, signature: &[&str]) -> Self {
+        let mut doc = MethodDoc::default();
+        doc.help(help).signature(signature.iter().copied());
+        doc
+    }
+}
+
+const BUILTINS: [&str; 5] = [
+    "system.getCapabilities",
+    "system.listMethods",
+    "system.methodHelp",
+    "system.methodSignature",
+    "system.multicall",
+];
+
+// (name, specUrl, specVersion) of every spec the server follows.
+const CAPABILITIES: [(&str, &str, i32); 4] = [
+    ("xmlrpc", "http://www.xmlrpc.com/spec", 1),
+    (
+        "faults_interop",
+        "http://xmlrpc-epi.sourceforge.net/specs/rfc.fault_codes.php",
+        20010516,
+    ),
+    (
+        "introspection",
+        "http://xmlrpc-epi.sourceforge.net/specs/rfc.introspection.php",
+        20010516,
+    ),
+    (
+        "system.multicall",
+        "http://www.xmlrpc.com/discuss/msgReader$1208",
+        1,
+    ),
+];
+
+impl Registry {
+    pub(super) fn list_methods(&self) -> XmlResponse {
+        let mut names: Vec<&str> = self.handlers.keys().map(String::as_str).collect();
+        names.extend(BUILTINS);
+        names.sort_unstable();
+        names.dedup();
+        let names = names
+            .into_iter()
+            .map(|name| Value::String(name.to_owned()))
+            .collect();
+        Ok(Params::new(vec![Value::to_array(names)]))
+    }
+
+    pub(super) fn method_help(&self, params: Params) -> XmlResponse {
+        let doc = self.method_doc(params)?;
+        Ok(Params::new(vec![Value::String(doc.help)]))
+    }
+
+    pub(super) fn method_signature(&self, params: Params) -> XmlResponse {
+        let doc = self.method_doc(params)?;
+        let signatures = match doc.signatures.is_empty() {
+            true => Value::String("undef".to_owned()),
+            false => Value::to_array(
+                doc.signatures
+                    .into_iter()
+                    .map(|types| Value::to_array(types.into_iter().map(Value::String).collect()))
+                    .collect(),
+            ),
+        };
+        Ok(Params::new(vec![signatures]))
+    }
+
+    pub(super) fn capabilities(&self) -> XmlResponse {
+        let members = CAPABILITIES
+            .iter()
+            .map(|&(name, url, version)| {
+                let spec = Value::to_struct(vec![
+                    Member::new("specUrl", Value::String(url.to_owned())),
+                    Member::new("specVersion", Value::Int(version)),
+                ]);
+                Member::new(name, spec)
+            })
+            .collect();
+        Ok(Params::new(vec![Value::to_struct(members)]))
+    }
+
+    // Registered methods take precedence over the built-in ones, as they do when called.
+    fn method_doc(&self, params: Params) -> Result<MethodDoc, Fault> {
+        let name = from_params::<String>(params).map_err(|e| FaultCode::InvalidParams.fault(e))?;
+        if self.handlers.contains_key(&name) {
+            return Ok(self.docs.get(&name).cloned().unwrap_or_default());
+        }
+        let doc = match name.as_str() {
+            "system.getCapabilities" => MethodDoc::builtin(
+                "Return the specs this server follows, keyed by name.",
+                &["struct"],
+            ),
+            "system.listMethods" => MethodDoc::builtin(
+                "Return the names of the methods this server answers.",
+                &["array"],
+            ),
+            "system.methodHelp" => {
+                MethodDoc::builtin("Return the help text of a method.", &["string", "string"])
+            }
+            "system.methodSignature" => MethodDoc::builtin(
+                "Return the signatures of a method, each starting with its return type.",
+                &["array", "string"],
+            ),
+            "system.multicall" => MethodDoc::builtin(
+                "Run an array of {methodName, params} calls, and return each result or fault.",
+                &["array", "array"],
+            ),
+            _ => return Err(FaultCode::MethodNotFound.fault(&name)),
+        };
+        Ok(doc)
+    }
+}
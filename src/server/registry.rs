@@ -0,0 +1,161 @@
+use super::{CallContext, ContextHandler, Handler, HandlerMap, MethodDoc};
+use crate::xmlfmt::{Call, Fault, FaultCode, Params, Value, XmlResponse, from_params, to_value};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// Methods answered at an endpoint: registered handlers, their documentation,
+/// and the built-in `system.*` methods, which describe only this registry. See `Server::mount`.
+///
+/// ```no_run
+/// # use xml_rpc::{Fault, Server};
+/// # use xml_rpc::server::Registry;
+/// let mut admin = Registry::new();
+/// admin.register_typed("restart", |_: ()| -> Result<bool, Fault> { Ok(true) });
+/// let mut server = Server::new(8000).unwrap();
+/// server.mount("/admin", admin);
+/// ```
+// Shared with the threads running requests, and cloned on write
+// when registering while requests are in flight.
+#[derive(Clone, Default)]
+pub struct Registry {
+    pub(super) handlers: HandlerMap,
+    pub(super) docs: HashMap<String, MethodDoc>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a handler, and return its documentation for introspection to fill in.
+    pub fn register(&mut self, name: String, handler: Handler) -> &mut MethodDoc {
+        self.register_with_context(
+            name,
+            Arc::new(move |_: &CallContext, params| handler(params)),
+        )
+    }
+
+    /// Register a handler receiving the `CallContext` of each call along with its params.
+    pub fn register_with_context(
+        &mut self,
+        name: String,
+        handler: ContextHandler,
+    ) -> &mut MethodDoc {
+        self.handlers.insert(name.clone(), handler);
+        let doc = self.docs.entry(name).or_default();
+        *doc = MethodDoc::default();
+        doc
+    }
+
+    /// Register a handler taking typed arguments and returning a typed result,
+    /// as `Server::register_typed` does.
+    pub fn register_typed<T, Treq, Tres, F>(&mut self, name: T, handler: F) -> &mut MethodDoc
+    where
+        T: Into<String>,
+        Treq: DeserializeOwned,
+        Tres: Serialize,
+        F: Fn(Treq) -> Result<Tres, Fault> + Send + Sync + 'static,
+    {
+        self.register_typed_with_context(name, move |_: &CallContext, req| handler(req))
+    }
+
+    /// Register a typed handler receiving the `CallContext` of each call.
+    pub fn register_typed_with_context<T, Treq, Tres, F>(
+        &mut self,
+        name: T,
+        handler: F,
+    ) -> &mut MethodDoc
+    where
+        T: Into<String>,
+        Treq: DeserializeOwned,
+        Tres: Serialize,
+        F: Fn(&CallContext, Treq) -> Result<Tres, Fault> + Send + Sync + 'static,
+    {
+        let wrapped = move |context: &CallContext, params: Params| -> XmlResponse {
+            let req = from_params::<Treq>(params).map_err(|e| FaultCode::InvalidParams.fault(e))?;
+            let res = handler(context, req)?;
+            let value = to_value(&res).map_err(|e| FaultCode::InternalError.fault(e))?;
+            Ok(Params::new(vec![value]))
+        };
+        self.register_with_context(name.into(), Arc::new(wrapped))
+    }
+
+    /// Invoke subscribers matching handler names.
+    /// Use this as a way to invoke methods or function on the python side of environment.
+    /// Built-in `system.*` methods answer when no handler is registered under their name.
+    pub(crate) fn handle(&self, req: Call, context: &CallContext) -> XmlResponse {
+        match self.handlers.get(&req.name) {
+            // A panicking handler fails its own call, not the thread serving requests,
+            // nor the other calls of a multicall.
+            Some(v) => panic::catch_unwind(AssertUnwindSafe(|| v(context, req.params)))
+                .unwrap_or_else(|_| Err(FaultCode::InternalError.fault("the method panicked"))),
+            None => self.handle_builtin(req, context),
+        }
+    }
+
+    fn handle_builtin(&self, req: Call, context: &CallContext) -> XmlResponse {
+        match req.name.as_str() {
+            "system.getCapabilities" => self.capabilities(),
+            "system.listMethods" => self.list_methods(),
+            "system.methodHelp" => self.method_help(req.params),
+            "system.methodSignature" => self.method_signature(req.params),
+            "system.multicall" => self.multicall(req.params, context),
+            _ => Err(FaultCode::MethodNotFound.fault(&req.name)),
+        }
+    }
+
+    /*
+        system.multicall takes a single array of calls, each a struct
+        { methodName: string, params: array }, and answers with an array holding, for each call,
+        either its result wrapped in a one-element array, or its fault struct.
+    */
+    fn multicall(&self, params: Params, context: &CallContext) -> XmlResponse {
+        let calls = match params.values() {
+            [Value::Array(data)] => data.values().to_vec(),
+            _ => {
+                return Err(FaultCode::InvalidParams
+                    .fault("system.multicall takes a single array of calls"));
+            }
+        };
+        let results = calls
+            .into_iter()
+            .map(|call| match self.multicall_entry(call, context) {
+                Ok(params) => Value::to_array(params.into()),
+                Err(fault) => fault.into(),
+            })
+            .collect();
+        Ok(Params::new(vec![Value::to_array(results)]))
+    }
+
+    fn multicall_entry(&self, call: Value, context: &CallContext) -> XmlResponse {
+        let members = match call {
+            Value::Struct { member } => *member,
+            _ => return Err(FaultCode::InvalidRequest.fault("multicall entry must be a struct")),
+        };
+        let mut name = None;
+        let mut params = Vec::new();
+        for member in members {
+            match member.into_pair() {
+                (key, Value::String(value)) if key == "methodName" => name = Some(value),
+                (key, Value::Array(data)) if key == "params" => params = (*data).into(),
+                (key, _) => {
+                    return Err(FaultCode::InvalidRequest
+                        .fault(format!("unexpected multicall entry member {:?}", key)));
+                }
+            }
+        }
+        match name {
+            Some(name) if name == "system.multicall" => {
+                Err(FaultCode::InvalidRequest.fault("recursive system.multicall is not allowed"))
+            }
+            Some(name) => {
+                let context = context.with_method_name(&name);
+                self.handle(Call::new(name, Params::new(params)), &context)
+            }
+            None => Err(FaultCode::InvalidRequest.fault("multicall entry without methodName")),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use tiny_http::Server as TinyHttpServer;
+
+/// Stops `Server::serve_forever` and `Server::run_until` from any thread.
+///
+/// Obtained from `Server::shutdown_handle`. Every clone stops the same server,
+/// and the requests already handed to the executor still get their response.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    requested: Arc<AtomicBool>,
+    // Weak, so a handle kept around does not keep the listener open.
+    server: Weak<TinyHttpServer>,
+}
+
+impl ShutdownHandle {
+    pub(super) fn new(requested: Arc<AtomicBool>, server: &Arc<TinyHttpServer>) -> Self {
+        Self {
+            requested,
+            server: Arc::downgrade(server),
+        }
+    }
+
+    pub fn shutdown(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        // Wake up the run loop if it is waiting for a request.
+        if let Some(server) = self.server.upgrade() {
+            server.unblock();
+        }
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,622 @@
+use crate::xmlfmt::{XmlError, XmlResult};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig, ServerConnection};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/*
+    HTTPS in front of the HTTP server, for `Server::bind_https_with`.
+    tiny_http only speaks TLS through a rustls config it builds itself, without client
+    authentication nor access to the certificates of clients, so the server listens
+    on an ephemeral port of localhost instead, and the front:
+    - accepts connections on the public address, each on its own thread,
+      up to `Limits::connections` at once, closing the others right away
+    - runs the handshake, verifying the certificate of the client when asked to
+    - relays the decrypted stream to the HTTP server over a connection of localhost,
+      until either side closes it, or nothing goes through for `Limits::idle`
+    The HTTP server sees the relaying connection as the caller: the front records which client
+    each one stands for, and requests reaching the server any other way are refused.
+*/
+
+// Time a client has to complete its handshake before it is dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const BUFFER_SIZE: usize = 16 * 1024;
+const MAX_CONNECTIONS: usize = 256;
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Certificate of an HTTPS server, and how it checks the certificates of its clients.
+/// See `Server::bind_https_with`.
+///
+/// ```no_run
+/// # use xml_rpc::Server;
+/// # use xml_rpc::server::TlsConfig;
+/// let config = TlsConfig::new(
+///     &std::fs::read("server.pem").unwrap(),
+///     &std::fs::read("server.key").unwrap(),
+/// )
+/// .unwrap()
+/// .client_certificates(&std::fs::read("clients-ca.pem").unwrap(), true)
+/// .unwrap();
+/// let server = Server::bind_https_with("0.0.0.0:8443", config).unwrap();
+/// ```
+pub struct TlsConfig {
+    certificates: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    client_roots: Option<RootCertStore>,
+    client_certificate_required: bool,
+}
+
+impl TlsConfig {
+    /// A PEM certificate chain and its PEM private key: PKCS #8 (`BEGIN PRIVATE KEY`),
+    /// RSA (`BEGIN RSA PRIVATE KEY`) or SEC1 (`BEGIN EC PRIVATE KEY`).
+    /// Fails unless the key is the one of the first certificate.
+    pub fn new(cert_pem: &[u8], key_pem: &[u8]) -> XmlResult<TlsConfig> {
+        let config = TlsConfig {
+            certificates: certificates(cert_pem, "cert_pem")?,
+            key: PrivateKeyDer::from_pem_slice(key_pem)
+                .map_err(|e| tls_error(format!("no private key in key_pem: {}", e)))?,
+            client_roots: None,
+            client_certificate_required: false,
+        };
+        config.server_config()?;
+        Ok(config)
+    }
+
+    /// Ask clients for a certificate, and accept only those issued by one of the certificates
+    /// of the PEM bundle `ca_pem`. Clients presenting none are refused when `required`,
+    /// and let in otherwise, for an `Authenticator` to check them.
+    /// Handlers get the certificate of a client from `CallContext::peer_certificate`.
+    pub fn client_certificates(mut self, ca_pem: &[u8], required: bool) -> XmlResult<TlsConfig> {
+        let mut roots = RootCertStore::empty();
+        for certificate in certificates(ca_pem, "ca_pem")? {
+            roots
+                .add(certificate)
+                .map_err(|e| tls_error(format!("invalid certificate in ca_pem: {}", e)))?;
+        }
+        self.client_roots = Some(roots);
+        self.client_certificate_required = required;
+        Ok(self)
+    }
+
+    pub(super) fn server_config(&self) -> XmlResult<Arc<ServerConfig>> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| tls_error(e.to_string()))?;
+        let builder = match &self.client_roots {
+            None => builder.with_no_client_auth(),
+            Some(roots) => {
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots.clone()), provider);
+                let verifier = match self.client_certificate_required {
+                    true => verifier,
+                    false => verifier.allow_unauthenticated(),
+                };
+                builder.with_client_cert_verifier(
+                    verifier.build().map_err(|e| tls_error(e.to_string()))?,
+                )
+            }
+        };
+        let config = builder
+            .with_single_cert(self.certificates.clone(), self.key.clone_key())
+            .map_err(|e| tls_error(format!("invalid certificate or key: {}", e)))?;
+        Ok(Arc::new(config))
+    }
+}
+
+// The private key is left out, so that it is not logged.
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("certificates", &self.certificates.len())
+            .field("client_roots", &self.client_roots.as_ref().map(|r| r.len()))
+            .field(
+                "client_certificate_required",
+                &self.client_certificate_required,
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+// Every certificate of a PEM bundle, which must hold at least one.
+fn certificates(pem: &[u8], name: &str) -> XmlResult<Vec<CertificateDer<'static>>> {
+    let certificates = CertificateDer::pem_slice_iter(pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| tls_error(format!("invalid {}: {}", name, e)))?;
+    match certificates.is_empty() {
+        true => Err(tls_error(format!("no certificate in {}", name))),
+        false => Ok(certificates),
+    }
+}
+
+fn tls_error(message: String) -> XmlError {
+    XmlError::Server(message)
+}
+
+// Client a relaying connection stands for.
+#[derive(Debug, Clone)]
+pub(super) struct Peer {
+    pub(super) addr: SocketAddr,
+    // DER certificate of the client, when it presented one.
+    pub(super) certificate: Option<Arc<[u8]>>,
+}
+
+// Clients of the open relaying connections, by the address they reach the HTTP server from.
+type Peers = Arc<Mutex<HashMap<SocketAddr, Peer>>>;
+
+// Bounds on the connections a front relays.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Limits {
+    // Connections relayed at once, each taking two threads once past its handshake.
+    pub(super) connections: usize,
+    // Time a connection is kept open without anything going through it, either way.
+    pub(super) idle: Duration,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            connections: MAX_CONNECTIONS,
+            idle: IDLE_TIMEOUT,
+        }
+    }
+}
+
+// Accepts HTTPS connections until dropped, along with the `Server` holding it.
+pub(super) struct Front {
+    addr: SocketAddr,
+    peers: Peers,
+    open: Arc<AtomicUsize>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Front {
+    // Relay the connections to `listener` to the HTTP server listening on `backend`.
+    pub(super) fn start(
+        listener: TcpListener,
+        config: Arc<ServerConfig>,
+        backend: SocketAddr,
+        limits: Limits,
+    ) -> io::Result<Front> {
+        let front = Front {
+            addr: listener.local_addr()?,
+            peers: Peers::default(),
+            open: Arc::default(),
+            closed: Arc::default(),
+        };
+        let peers = Arc::clone(&front.peers);
+        let open = Arc::clone(&front.open);
+        let closed = Arc::clone(&front.closed);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if closed.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    // Such as too many open files, which may not last.
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                };
+                // Closed by dropping it, when as many are relayed already.
+                let Some(slot) = Slot::take(&open, limits.connections) else {
+                    continue;
+                };
+                let config = Arc::clone(&config);
+                let peers = Arc::clone(&peers);
+                // Fails when the handshake does, or the client goes away: there is nobody to tell.
+                thread::spawn(move || {
+                    let _slot = slot;
+                    serve(stream, config, backend, limits.idle, &peers)
+                });
+            }
+        });
+        Ok(front)
+    }
+
+    pub(super) fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // Connections being served, handshakes included.
+    #[cfg(test)]
+    fn connections(&self) -> usize {
+        self.open.load(Ordering::SeqCst)
+    }
+
+    // Whether no connection is being relayed.
+    #[cfg(test)]
+    pub(super) fn is_idle(&self) -> bool {
+        lock(&self.peers).is_empty()
+    }
+
+    // Client of the request reaching the HTTP server from `addr`,
+    // `None` when it did not come through the front.
+    pub(super) fn peer(&self, addr: Option<&SocketAddr>) -> Option<Peer> {
+        addr.and_then(|addr| lock(&self.peers).get(addr).cloned())
+    }
+}
+
+impl Drop for Front {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // Wake the accepting thread up, so that it sees it is closed.
+        let mut addr = self.addr;
+        if addr.ip().is_unspecified() {
+            addr.set_ip(match addr {
+                SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+                SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+            });
+        }
+        let _ = TcpStream::connect_timeout(&addr, Duration::from_secs(1));
+    }
+}
+
+// One of the connections a front relays at once, given back when dropped.
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(open: &Arc<AtomicUsize>, max: usize) -> Option<Slot> {
+        open.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            (n < max).then_some(n + 1)
+        })
+        .ok()?;
+        Some(Slot(Arc::clone(open)))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn serve(
+    mut client: TcpStream,
+    config: Arc<ServerConfig>,
+    backend: SocketAddr,
+    idle: Duration,
+    peers: &Mutex<HashMap<SocketAddr, Peer>>,
+) -> io::Result<()> {
+    let addr = client.peer_addr()?;
+    let mut connection = ServerConnection::new(config).map_err(io::Error::other)?;
+    // Responses are flushed to the client as soon as they are written, see `respond`.
+    connection.set_buffer_limit(None);
+    client.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    client.set_write_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    while connection.is_handshaking() {
+        connection.complete_io(&mut client)?;
+    }
+    // A client that stops reading its responses is given up as well.
+    client.set_read_timeout(Some(idle))?;
+    client.set_write_timeout(Some(idle))?;
+    let certificate = connection
+        .peer_certificates()
+        .and_then(|chain| chain.first())
+        .map(|certificate| Arc::from(certificate.as_ref()));
+
+    let server = TcpStream::connect(backend)?;
+    let relay_addr = server.local_addr()?;
+    let peer = Peer { addr, certificate };
+    lock(peers).insert(relay_addr, peer);
+    let result = relay(connection, client, server, idle);
+    lock(peers).remove(&relay_addr);
+    result
+}
+
+// A poisoned lock is still consistent here: every update under it is a single call.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// State shared by both directions of a relayed connection.
+struct Relay {
+    connection: Mutex<ServerConnection>,
+    // TLS records waiting to be sent to the client, in the order rustls wrote them.
+    outbox: Mutex<Vec<u8>>,
+    // Held while sending the outbox, so that records written meanwhile wait for their turn.
+    client: Mutex<TcpStream>,
+    last_active: Mutex<Instant>,
+    idle: Duration,
+}
+
+impl Relay {
+    // Move what rustls has to send to the outbox. Called with the connection locked,
+    // which is what keeps the records in order: the outbox is sent by `flush`, after unlocking.
+    fn queue(&self, connection: &mut ServerConnection) -> io::Result<()> {
+        let mut outbox = lock(&self.outbox);
+        while connection.wants_write() {
+            connection.write_tls(&mut *outbox)?;
+        }
+        Ok(())
+    }
+
+    // Send the outbox to the client, without blocking the other direction meanwhile.
+    fn flush(&self) -> io::Result<()> {
+        let mut client = lock(&self.client);
+        let records = mem::take(&mut *lock(&self.outbox));
+        client.write_all(&records)
+    }
+
+    fn touch(&self) {
+        *lock(&self.last_active) = Instant::now();
+    }
+
+    // Time left before the connection is idle, `None` once it is.
+    fn idle_left(&self) -> Option<Duration> {
+        let left = self.idle.checked_sub(lock(&self.last_active).elapsed())?;
+        (!left.is_zero()).then_some(left)
+    }
+}
+
+// Decrypt what the client sends to the server, and encrypt what the server answers,
+// until either side closes its connection, or the connection is idle.
+fn relay(
+    connection: ServerConnection,
+    client: TcpStream,
+    server: TcpStream,
+    idle: Duration,
+) -> io::Result<()> {
+    let relay = Arc::new(Relay {
+        connection: Mutex::new(connection),
+        outbox: Mutex::default(),
+        client: Mutex::new(client.try_clone()?),
+        last_active: Mutex::new(Instant::now()),
+        idle,
+    });
+    let responses = {
+        let relay = Arc::clone(&relay);
+        let client = client.try_clone()?;
+        let mut server = server.try_clone()?;
+        thread::spawn(move || {
+            let result = respond(&relay, &mut server);
+            // Stops `forward` waiting for a client that has nothing left to read.
+            let _ = client.shutdown(Shutdown::Both);
+            result
+        })
+    };
+    let result = forward(&relay, &client, &server);
+    // The server answers the requests it has read, then closes the connection.
+    let _ = server.shutdown(Shutdown::Write);
+    if result.is_err() {
+        let _ = server.shutdown(Shutdown::Both);
+    }
+    let responded = responses
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("relay panicked")));
+    result.and(responded)
+}
+
+// Relay requests, from the client to the server. Returns once the client is idle,
+// so that the server closes the connection after answering what it has read.
+fn forward(relay: &Relay, mut client: &TcpStream, mut server: &TcpStream) -> io::Result<()> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        let (plaintext, open) = {
+            let mut connection = lock(&relay.connection);
+            let mut plaintext = Vec::new();
+            let open = read_plaintext(&mut connection, &mut plaintext)?;
+            (plaintext, open)
+        };
+        server.write_all(&plaintext)?;
+        if !open {
+            return Ok(());
+        }
+        let read = match client.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            // Responses going through keep the connection open too.
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                match relay.idle_left() {
+                    Some(left) => {
+                        client.set_read_timeout(Some(left))?;
+                        continue;
+                    }
+                    None => return Ok(()),
+                }
+            }
+            Err(e) => return Err(e),
+        };
+        relay.touch();
+        client.set_read_timeout(Some(relay.idle))?;
+        let received = {
+            let mut connection = lock(&relay.connection);
+            let received = receive(&mut connection, &buffer[..read]);
+            // Along with handshake messages, the alert telling the client what went wrong.
+            relay.queue(&mut connection)?;
+            received
+        };
+        let flushed = relay.flush();
+        received.and(flushed)?;
+    }
+}
+
+// Hand what the client has sent to rustls.
+fn receive(connection: &mut ServerConnection, mut received: &[u8]) -> io::Result<()> {
+    while !received.is_empty() {
+        if connection.read_tls(&mut received)? == 0 {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        connection
+            .process_new_packets()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+    Ok(())
+}
+
+// Move what has been decrypted so far to `plaintext`. `false` once the client has closed.
+fn read_plaintext(connection: &mut ServerConnection, plaintext: &mut Vec<u8>) -> io::Result<bool> {
+    let mut chunk = [0; BUFFER_SIZE];
+    loop {
+        match connection.reader().read(&mut chunk) {
+            Ok(0) => return Ok(false),
+            Ok(read) => plaintext.extend_from_slice(&chunk[..read]),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(true),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Relay responses, from the server to the client.
+fn respond(relay: &Relay, server: &mut TcpStream) -> io::Result<()> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    loop {
+        let read = server.read(&mut buffer)?;
+        relay.touch();
+        {
+            let mut connection = lock(&relay.connection);
+            match read {
+                0 => connection.send_close_notify(),
+                _ => connection.writer().write_all(&buffer[..read])?,
+            }
+            relay.queue(&mut connection)?;
+        }
+        relay.flush()?;
+        if read == 0 {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::ServerName;
+    use rustls::{ClientConfig, ClientConnection, StreamOwned};
+
+    type Client = StreamOwned<ClientConnection, TcpStream>;
+
+    // A front relaying to a server echoing what it reads, and the certificate it answers with.
+    fn echo_front(limits: Limits) -> (Front, CertificateDer<'static>) {
+        let backend = TcpListener::bind("127.0.0.1:0").unwrap();
+        let backend_addr = backend.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in backend.incoming() {
+                let mut stream = stream.unwrap();
+                thread::spawn(move || {
+                    let _ = io::copy(&mut stream.try_clone().unwrap(), &mut stream);
+                    let _ = stream.shutdown(Shutdown::Write);
+                });
+            }
+        });
+        let certified = rcgen::generate_simple_self_signed(vec!["127.0.0.1".to_owned()]).unwrap();
+        let config = TlsConfig::new(
+            certified.cert.pem().as_bytes(),
+            certified.key_pair.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let front = Front::start(
+            listener,
+            config.server_config().unwrap(),
+            backend_addr,
+            limits,
+        )
+        .unwrap();
+        (front, certified.cert.der().clone())
+    }
+
+    fn connect(front: &Front, root: &CertificateDer<'static>) -> Client {
+        let mut roots = RootCertStore::empty();
+        roots.add(root.clone()).unwrap();
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let name = ServerName::try_from("127.0.0.1").unwrap();
+        let connection = ClientConnection::new(Arc::new(config), name).unwrap();
+        let stream = TcpStream::connect(front.local_addr()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        StreamOwned::new(connection, stream)
+    }
+
+    fn echo(client: &mut Client, message: &[u8]) -> Vec<u8> {
+        client.write_all(message).unwrap();
+        let mut answer = vec![0; message.len()];
+        client.read_exact(&mut answer).unwrap();
+        answer
+    }
+
+    fn wait_for(condition: impl Fn() -> bool) {
+        let start = Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn relays_both_ways() {
+        let (front, root) = echo_front(Limits::default());
+        let mut client = connect(&front, &root);
+        let message = vec![b'x'; 3 * BUFFER_SIZE];
+        assert_eq!(echo(&mut client, &message), message);
+        assert_eq!(echo(&mut client, b"again"), b"again");
+        drop(client);
+        wait_for(|| front.connections() == 0);
+    }
+
+    #[test]
+    fn refuses_connections_over_the_limit() {
+        let limits = Limits {
+            connections: 2,
+            ..Limits::default()
+        };
+        let (front, root) = echo_front(limits);
+        // Both stuck in their handshakes.
+        let stuck: Vec<_> = (0..2)
+            .map(|_| TcpStream::connect(front.local_addr()).unwrap())
+            .collect();
+        wait_for(|| front.connections() == 2);
+
+        let mut refused = connect(&front, &root);
+        let mut buffer = [0; 1];
+        assert!(refused.write_all(b"ping").is_err() || refused.read(&mut buffer).is_err());
+        assert_eq!(front.connections(), 2);
+
+        drop(stuck);
+        wait_for(|| front.connections() == 0);
+        let mut client = connect(&front, &root);
+        assert_eq!(echo(&mut client, b"ping"), b"ping");
+    }
+
+    #[test]
+    fn closes_idle_connections() {
+        let limits = Limits {
+            idle: Duration::from_millis(200),
+            ..Limits::default()
+        };
+        let (front, root) = echo_front(limits);
+        let mut client = connect(&front, &root);
+        assert_eq!(echo(&mut client, b"ping"), b"ping");
+        // Traffic keeps it open.
+        for _ in 0..4 {
+            thread::sleep(Duration::from_millis(100));
+            assert_eq!(echo(&mut client, b"ping"), b"ping");
+        }
+
+        let start = Instant::now();
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).unwrap();
+        assert!(rest.is_empty());
+        assert!(start.elapsed() < Duration::from_secs(2));
+        wait_for(|| front.is_idle() && front.connections() == 0);
+    }
+}
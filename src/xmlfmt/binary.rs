@@ -0,0 +1,87 @@
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::{DecodePaddingMode, Engine};
+use base64::{DecodeError, alphabet};
+
+/*
+    Base64 (RFC 4648) text used for <base64> values.
+    Writing always produces canonical, padded text on a single line.
+    Reading is lenient in what other implementations are known to send:
+    - line breaks and other whitespace anywhere in the text (Python wraps at 76 characters)
+    - missing `=` padding
+*/
+const ENGINE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+pub(crate) fn encode_base64(data: &[u8]) -> String {
+    ENGINE.encode(data)
+}
+
+// On failure, the message says what is wrong and at which offset of `text`.
+pub(crate) fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    // Keep the offset of every symbol in `text`, so errors point at the original input.
+    let (symbols, offsets): (Vec<u8>, Vec<usize>) = text
+        .bytes()
+        .enumerate()
+        .filter(|(_, b)| !b.is_ascii_whitespace())
+        .map(|(i, b)| (b, i))
+        .unzip();
+    ENGINE.decode(&symbols).map_err(|e| {
+        let message = match e {
+            DecodeError::InvalidByte(i, byte) => {
+                format!("invalid {} at offset {}", describe(byte), offsets[i])
+            }
+            DecodeError::InvalidLastSymbol { offset, symbol, .. } => format!(
+                "{} at offset {} has trailing bits set, the text looks truncated",
+                describe(symbol),
+                offsets[offset]
+            ),
+            DecodeError::InvalidLength(len) => format!(
+                "{} symbols cannot be the length of base64 text, the text looks truncated",
+                len
+            ),
+            DecodeError::InvalidPadding => "invalid padding at the end of the text".to_owned(),
+        };
+        format!("invalid base64: {}", message)
+    })
+}
+
+fn describe(byte: u8) -> String {
+    match byte {
+        0x21..=0x7e => format!("character '{}'", byte as char),
+        _ => format!("byte 0x{:02x}", byte),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_canonical_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(&[0xff, 0x00, 0xfe]), "/wD+");
+    }
+
+    #[test]
+    fn reads_wrapped_and_unpadded_base64() {
+        assert_eq!(decode_base64("Zm9v\nYmFy\n").unwrap(), b"foobar");
+        assert_eq!(decode_base64("  Zm 9v\r\n\tYg== ").unwrap(), b"foob");
+        assert_eq!(decode_base64("Zg").unwrap(), b"f");
+        assert_eq!(decode_base64("").unwrap(), b"");
+    }
+
+    #[test]
+    fn rejects_invalid_base64_with_its_offset() {
+        let error = decode_base64("Zm9v\nYm#y").unwrap_err();
+        assert!(error.contains("character '#' at offset 7"), "{}", error);
+
+        let error = decode_base64("Zm9vY").unwrap_err();
+        assert!(error.contains("truncated"), "{}", error);
+
+        assert!(decode_base64("Zg=a").is_err());
+    }
+}
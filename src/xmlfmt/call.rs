@@ -1,9 +1,9 @@
 use crate::xmlfmt::Params;
 use crate::xmlfmt::XmlResult;
+use crate::xmlfmt::parser::parse_call;
 use crate::xmlfmt::to_xml::ToXml;
-use crate::xmlfmt::{FmtError, XmlError};
+use crate::xmlfmt::writer::write_call;
 use serde::{Deserialize, Serialize};
-use serde_xml_rs::to_string;
 
 // Call is the method to invoke methods on python side. Keep it.
 /*
@@ -11,7 +11,10 @@ use serde_xml_rs::to_string;
     <?xml version="1.0"?>
     <methodCall>
         <methodName>{name}</methodName>
-        <params>{params}</params> | could also be <params />
+        <params>
+            <param><value>{value}</value></param>
+            ...
+        </params>
     </methodCall>
 */
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -35,17 +38,14 @@ impl Call {
 
     #[allow(dead_code)]
     pub fn from_xml(data: &str) -> XmlResult<Call> {
-        // strip away <?xml keyword, or prefix/suffix content
-        let result = serde_xml_rs::from_str::<Call>(&data).map_err(|e|
-                // FEATURE: Fault code is application specific, consider making new enum for our own fault codes
-                XmlError::Format(FmtError::Decoding(e.to_string())));
-        result
+        // Reading a document directly accepts every type `Value` can hold, extensions included.
+        parse_call(data.as_bytes(), true)
     }
 }
 
 impl ToXml for Call {
     fn to_xml(&self) -> XmlResult<String> {
-        to_string(&self).map_err(|e| XmlError::Format(FmtError::Encoding(e.to_string())))
+        write_call(self)
     }
 }
 
@@ -53,15 +53,16 @@ impl ToXml for Call {
 mod tests {
     use crate::xmlfmt::*;
 
-    pub fn ser_and_de_call_value(value: Call) {
+    pub fn ser_and_de_call_value(value: Call, expected: &str) {
         let data = value.to_xml();
         assert!(
             data.is_ok(),
             "Fail to serialize Call to XML: {}",
             data.unwrap_err()
         );
-        println!("{:?}", &data);
-        let result = Call::from_xml(&data.unwrap());
+        let data = data.unwrap();
+        assert_eq!(data, expected);
+        let result = Call::from_xml(&data);
         assert!(
             result.is_ok(),
             "Failed to deserialize Call from XML: {}",
@@ -79,11 +80,46 @@ mod tests {
         let name = String::from("foobar");
         let call = Call::new(name, params);
 
-        ser_and_de_call_value(call);
+        ser_and_de_call_value(
+            call,
+            concat!(
+                r#"<?xml version="1.0"?>"#,
+                "<methodCall><methodName>foobar</methodName><params>",
+                "<param><value><int>42</int></value></param>",
+                "<param><value><string>baz</string></value></param>",
+                "</params></methodCall>",
+            ),
+        );
     }
 
     #[test]
     fn reads_and_writes_empty_call() {
-        ser_and_de_call_value(Call::new(String::new(), Default::default()));
+        ser_and_de_call_value(
+            Call::new(String::new(), Default::default()),
+            concat!(
+                r#"<?xml version="1.0"?>"#,
+                "<methodCall><methodName></methodName><params></params></methodCall>",
+            ),
+        );
+    }
+
+    #[test]
+    fn writes_call_without_arguments() {
+        #[derive(Serialize)]
+        struct Nothing;
+
+        let expected = concat!(
+            r#"<?xml version="1.0"?>"#,
+            "<methodCall><methodName>ping</methodName><params></params></methodCall>",
+        );
+        for params in [to_params(&()).unwrap(), to_params(&Nothing).unwrap()] {
+            assert_eq!(Call::new("ping", params).to_xml().unwrap(), expected);
+        }
+        // Empty structs and maps are still sent as a single param.
+        let map = std::collections::BTreeMap::<String, i32>::new();
+        assert_eq!(to_params(&map).unwrap().values(), [Value::to_struct(Vec::new())]);
+        // And no params read back as `()`.
+        let call = Call::from_xml(expected).unwrap();
+        assert_eq!(from_params::<()>(call.params).unwrap(), ());
     }
 }
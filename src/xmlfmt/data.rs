@@ -1,4 +1,4 @@
-use crate::xmlfmt::Param;
+use crate::xmlfmt::{Param, Value};
 use serde::{Deserialize, Serialize};
 
 /*
@@ -22,10 +22,14 @@ impl Data {
     pub fn new(value: Param) -> Self {
         Self { value }
     }
+
+    pub fn values(&self) -> &[Value] {
+        &self.value
+    }
 }
 
-impl Into<Param> for Data {
-    fn into(self) -> Param {
-        self.value
+impl From<Data> for Param {
+    fn from(data: Data) -> Self {
+        data.value
     }
 }
\ No newline at end of file
@@ -0,0 +1,527 @@
+use crate::xmlfmt::{FmtError, XmlError, XmlResult};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Name of the newtype struct XmlDateTime serializes into, so `ser::Serializer` can write a
+// <dateTime.iso8601> instead of a <string>. Other serde formats just see the string inside.
+pub(crate) const DATETIME_TOKEN: &str = "$xml_rpc::XmlDateTime";
+
+/*
+    Value of a <dateTime.iso8601> element.
+    XML-RPC does not carry a timezone, so this is a "naive" date and time with a one second
+    resolution. Conversions from and to absolute times (`SystemTime`, and chrono/time types
+    behind their cargo features) treat it as UTC.
+
+    Both the basic form from the spec and the dashed (extended) form are read:
+    - 19980717T14:08:55
+    - 1998-07-17T14:08:55
+    - 19980717T140855
+    A trailing `Z` is accepted and ignored. Writing always uses the basic form of the spec.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct XmlDateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl XmlDateTime {
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> XmlResult<Self> {
+        Self::checked(year, month, day, hour, minute, second).map_err(invalid)
+    }
+
+    pub fn parse(text: &str) -> XmlResult<Self> {
+        Self::parse_str(text).map_err(invalid)
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Format in the dashed form, `1998-07-17T14:08:55`.
+    pub fn format_dashed(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
+    fn checked(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<Self, String> {
+        if year > 9999 {
+            return Err(format!("year {} does not fit in four digits", year));
+        }
+        if !(1..=12).contains(&month) {
+            return Err(format!("month {} is out of range", month));
+        }
+        if day == 0 || day > days_in_month(year, month) {
+            return Err(format!(
+                "day {} is out of range for {:04}-{:02}",
+                day, year, month
+            ));
+        }
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(format!(
+                "time {:02}:{:02}:{:02} is out of range",
+                hour, minute, second
+            ));
+        }
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    // On failure, the message says what is wrong with `text`.
+    pub(crate) fn parse_str(text: &str) -> Result<Self, String> {
+        let fail = || format!("{:?} is not an ISO 8601 date and time", text);
+        if !text.is_ascii() {
+            return Err(fail());
+        }
+        let trimmed = text.strip_suffix('Z').unwrap_or(text);
+        let (date, time) = trimmed.split_once('T').ok_or_else(fail)?;
+        let date = match date.len() {
+            8 => [&date[0..4], &date[4..6], &date[6..8]],
+            10 if &date[4..5] == "-" && &date[7..8] == "-" => {
+                [&date[0..4], &date[5..7], &date[8..10]]
+            }
+            _ => return Err(fail()),
+        };
+        let time = match time.len() {
+            6 => [&time[0..2], &time[2..4], &time[4..6]],
+            8 if &time[2..3] == ":" && &time[5..6] == ":" => {
+                [&time[0..2], &time[3..5], &time[6..8]]
+            }
+            _ => return Err(fail()),
+        };
+        let number = |digits: &str| -> Result<u16, String> {
+            if digits.bytes().all(|b| b.is_ascii_digit()) {
+                digits.parse::<u16>().map_err(|_| fail())
+            } else {
+                Err(fail())
+            }
+        };
+        Self::checked(
+            number(date[0])?,
+            number(date[1])? as u8,
+            number(date[2])? as u8,
+            number(time[0])? as u8,
+            number(time[1])? as u8,
+            number(time[2])? as u8,
+        )
+    }
+
+    // Seconds since 1970-01-01T00:00:00, negative before it.
+    fn unix_seconds(&self) -> i64 {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+    }
+
+    fn from_unix_seconds(seconds: i64) -> Result<Self, String> {
+        let days = seconds.div_euclid(86_400);
+        let time = seconds.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=9999).contains(&year) {
+            return Err(format!("year {} does not fit in four digits", year));
+        }
+        Self::checked(
+            year as u16,
+            month,
+            day,
+            (time / 3_600) as u8,
+            (time % 3_600 / 60) as u8,
+            (time % 60) as u8,
+        )
+    }
+}
+
+fn invalid(message: String) -> XmlError {
+    XmlError::Format(FmtError::Decoding(format!(
+        "invalid dateTime.iso8601: {}",
+        message
+    )))
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date, from Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u8;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl fmt::Display for XmlDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}{:02}{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+impl FromStr for XmlDateTime {
+    type Err = XmlError;
+
+    fn from_str(s: &str) -> XmlResult<Self> {
+        Self::parse(s)
+    }
+}
+
+impl From<XmlDateTime> for SystemTime {
+    fn from(datetime: XmlDateTime) -> Self {
+        let seconds = datetime.unix_seconds();
+        if seconds >= 0 {
+            UNIX_EPOCH + Duration::from_secs(seconds as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+        }
+    }
+}
+
+// Sub-second precision is truncated, as XML-RPC cannot carry it.
+impl TryFrom<SystemTime> for XmlDateTime {
+    type Error = XmlError;
+
+    fn try_from(time: SystemTime) -> XmlResult<Self> {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => i64::try_from(after.as_secs()).map_err(|e| invalid(e.to_string()))?,
+            Err(before) => {
+                let before = before.duration();
+                let mut seconds =
+                    -i64::try_from(before.as_secs()).map_err(|e| invalid(e.to_string()))?;
+                if before.subsec_nanos() > 0 {
+                    seconds -= 1;
+                }
+                seconds
+            }
+        };
+        Self::from_unix_seconds(seconds).map_err(invalid)
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_support {
+    use super::{XmlDateTime, invalid};
+    use crate::xmlfmt::XmlError;
+    use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike, Utc};
+
+    impl From<XmlDateTime> for NaiveDateTime {
+        fn from(datetime: XmlDateTime) -> Self {
+            NaiveDate::from_ymd_opt(
+                i32::from(datetime.year),
+                u32::from(datetime.month),
+                u32::from(datetime.day),
+            )
+            .and_then(|date| {
+                date.and_hms_opt(
+                    u32::from(datetime.hour),
+                    u32::from(datetime.minute),
+                    u32::from(datetime.second),
+                )
+            })
+            .expect("XmlDateTime is always a valid date and time")
+        }
+    }
+
+    impl From<XmlDateTime> for DateTime<Utc> {
+        fn from(datetime: XmlDateTime) -> Self {
+            NaiveDateTime::from(datetime).and_utc()
+        }
+    }
+
+    // Sub-second precision is truncated, as XML-RPC cannot carry it.
+    impl TryFrom<NaiveDateTime> for XmlDateTime {
+        type Error = XmlError;
+
+        fn try_from(datetime: NaiveDateTime) -> Result<Self, XmlError> {
+            let year = u16::try_from(datetime.year())
+                .map_err(|_| invalid(format!("year {} is out of range", datetime.year())))?;
+            XmlDateTime::checked(
+                year,
+                datetime.month() as u8,
+                datetime.day() as u8,
+                datetime.hour() as u8,
+                datetime.minute() as u8,
+                // a leap second is folded into the second before it
+                datetime.second().min(59) as u8,
+            )
+            .map_err(invalid)
+        }
+    }
+
+    impl TryFrom<DateTime<Utc>> for XmlDateTime {
+        type Error = XmlError;
+
+        fn try_from(datetime: DateTime<Utc>) -> Result<Self, XmlError> {
+            XmlDateTime::try_from(datetime.naive_utc())
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_support {
+    use super::{XmlDateTime, invalid};
+    use crate::xmlfmt::XmlError;
+    use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+    impl From<XmlDateTime> for PrimitiveDateTime {
+        fn from(datetime: XmlDateTime) -> Self {
+            let month = Month::try_from(datetime.month).expect("XmlDateTime has a valid month");
+            let date = Date::from_calendar_date(i32::from(datetime.year), month, datetime.day)
+                .expect("XmlDateTime is always a valid date");
+            let time = Time::from_hms(datetime.hour, datetime.minute, datetime.second)
+                .expect("XmlDateTime is always a valid time");
+            PrimitiveDateTime::new(date, time)
+        }
+    }
+
+    impl From<XmlDateTime> for OffsetDateTime {
+        fn from(datetime: XmlDateTime) -> Self {
+            PrimitiveDateTime::from(datetime).assume_utc()
+        }
+    }
+
+    // Sub-second precision is truncated, as XML-RPC cannot carry it.
+    impl TryFrom<PrimitiveDateTime> for XmlDateTime {
+        type Error = XmlError;
+
+        fn try_from(datetime: PrimitiveDateTime) -> Result<Self, XmlError> {
+            let year = u16::try_from(datetime.year())
+                .map_err(|_| invalid(format!("year {} is out of range", datetime.year())))?;
+            XmlDateTime::checked(
+                year,
+                u8::from(datetime.month()),
+                datetime.day(),
+                datetime.hour(),
+                datetime.minute(),
+                datetime.second(),
+            )
+            .map_err(invalid)
+        }
+    }
+
+    // The time is converted to UTC first.
+    impl TryFrom<OffsetDateTime> for XmlDateTime {
+        type Error = XmlError;
+
+        fn try_from(datetime: OffsetDateTime) -> Result<Self, XmlError> {
+            let utc = datetime.to_offset(UtcOffset::UTC);
+            XmlDateTime::try_from(PrimitiveDateTime::new(utc.date(), utc.time()))
+        }
+    }
+}
+
+impl Serialize for XmlDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(DATETIME_TOKEN, &self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for XmlDateTime {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(DATETIME_TOKEN, XmlDateTimeVisitor)
+    }
+}
+
+struct XmlDateTimeVisitor;
+
+impl<'de> Visitor<'de> for XmlDateTimeVisitor {
+    type Value = XmlDateTime;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an ISO 8601 date and time such as 19980717T14:08:55")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<XmlDateTime, E> {
+        XmlDateTime::parse_str(v).map_err(E::custom)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, d: D) -> Result<XmlDateTime, D::Error> {
+        d.deserialize_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xmlfmt::{Value, from_value, to_value};
+
+    fn datetime(text: &str) -> XmlDateTime {
+        XmlDateTime::parse(text).unwrap()
+    }
+
+    #[test]
+    fn reads_basic_and_dashed_forms() {
+        let expected = XmlDateTime::new(1998, 7, 17, 14, 8, 55).unwrap();
+        assert_eq!(datetime("19980717T14:08:55"), expected);
+        assert_eq!(datetime("1998-07-17T14:08:55"), expected);
+        assert_eq!(datetime("19980717T140855"), expected);
+        assert_eq!(datetime("19980717T14:08:55Z"), expected);
+    }
+
+    #[test]
+    fn writes_basic_and_dashed_forms() {
+        let value = datetime("1998-07-17T14:08:55");
+        assert_eq!(value.to_string(), "19980717T14:08:55");
+        assert_eq!(value.format_dashed(), "1998-07-17T14:08:55");
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        assert!(XmlDateTime::parse("").is_err());
+        assert!(XmlDateTime::parse("19980717").is_err());
+        assert!(XmlDateTime::parse("1998071T14:08:55").is_err());
+        assert!(XmlDateTime::parse("19980717T14-08-55").is_err());
+        assert!(XmlDateTime::parse("19981317T14:08:55").is_err());
+        assert!(XmlDateTime::parse("19990229T14:08:55").is_err());
+        assert!(XmlDateTime::parse("19980717T24:08:55").is_err());
+        assert!(XmlDateTime::parse("1998+717T14:08:55").is_err());
+        assert!(XmlDateTime::parse("1998é717T14:08:55").is_err());
+        assert!(XmlDateTime::parse("20000229T00:00:00").is_ok());
+    }
+
+    #[test]
+    fn converts_to_and_from_system_time() {
+        let epoch = datetime("19700101T00:00:00");
+        assert_eq!(SystemTime::from(epoch), UNIX_EPOCH);
+
+        let value = datetime("20240229T23:59:59");
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_251_199);
+        assert_eq!(SystemTime::from(value), time);
+        assert_eq!(XmlDateTime::try_from(time).unwrap(), value);
+
+        let before_epoch = datetime("19691231T23:59:59");
+        let time = UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(XmlDateTime::try_from(time).unwrap(), before_epoch);
+        assert_eq!(
+            SystemTime::from(before_epoch),
+            UNIX_EPOCH - Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn serializes_into_datetime_value() {
+        let value = datetime("19980717T14:08:55");
+        assert_eq!(to_value(&value).unwrap(), Value::DateTime(value));
+        assert_eq!(
+            from_value::<XmlDateTime>(Value::DateTime(value)).unwrap(),
+            value
+        );
+        assert_eq!(
+            from_value::<XmlDateTime>(Value::String("1998-07-17T14:08:55".into())).unwrap(),
+            value
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn converts_to_and_from_chrono() {
+        use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+        let value = datetime("19980717T14:08:55");
+        let naive = NaiveDate::from_ymd_opt(1998, 7, 17)
+            .unwrap()
+            .and_hms_opt(14, 8, 55)
+            .unwrap();
+        assert_eq!(NaiveDateTime::from(value), naive);
+        assert_eq!(XmlDateTime::try_from(naive).unwrap(), value);
+        assert_eq!(DateTime::<Utc>::from(value), naive.and_utc());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn converts_to_and_from_time() {
+        use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+        let value = datetime("19980717T14:08:55");
+        let primitive = PrimitiveDateTime::new(
+            Date::from_calendar_date(1998, Month::July, 17).unwrap(),
+            Time::from_hms(14, 8, 55).unwrap(),
+        );
+        assert_eq!(PrimitiveDateTime::from(value), primitive);
+        assert_eq!(XmlDateTime::try_from(primitive).unwrap(), value);
+
+        let offset = primitive.assume_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+        assert_eq!(
+            XmlDateTime::try_from(offset).unwrap(),
+            datetime("19980717T12:08:55")
+        );
+        assert_eq!(OffsetDateTime::from(value), primitive.assume_utc());
+    }
+}
@@ -0,0 +1,601 @@
+use crate::xmlfmt::{Member, Param, Params, Value, XmlError, XmlResult};
+use serde::de::{
+    self, DeserializeSeed, Deserializer as _, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    Unexpected, VariantAccess, Visitor,
+};
+use std::vec;
+
+/*
+    Deserializer that reads any `T: Deserialize` out of a `Value`.
+    This is the reverse of the mapping in `xmlfmt/README.md`:
+    - integers are read from `int`/`i4`, or parsed from a `string` (how `i64`, `u32` and `u64` are written)
+    - `option` is read from an array with zero or one element (`nil` is accepted as `None`)
+    - `unit` is read from an empty struct or `nil`
+    - enum variants are read from a struct with a single member named after the variant
+    - the extension integer and float types are read wherever a number is expected
+*/
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = XmlError;
+
+    fn deserialize_any<V>(self, visitor: V) -> XmlResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::I4(v) | Value::Int(v) => visitor.visit_i32(v),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::DateTime(v) => visitor.visit_string(v.to_string()),
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::Base64(v) => visitor.visit_byte_buf(v),
+            Value::Array(data) => visit_array(Param::from(*data), visitor),
+            Value::Struct { member } => visit_struct(*member, visitor),
+            Value::Nil => visitor.visit_unit(),
+            Value::I8(v) => visitor.visit_i64(v),
+            Value::I1(v) => visitor.visit_i8(v),
+            Value::I2(v) => visitor.visit_i16(v),
+            Value::Float(v) => visitor.visit_f32(v),
+            Value::BigDecimal(v) => visitor.visit_string(v),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_integer(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        match self {
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::I4(v) | Value::Int(v) => visitor.visit_f64(f64::from(v)),
+            Value::Float(v) => visitor.visit_f64(f64::from(v)),
+            Value::I1(v) => visitor.visit_f64(f64::from(v)),
+            Value::I2(v) => visitor.visit_f64(f64::from(v)),
+            Value::I8(v) => visitor.visit_i64(v),
+            Value::BigDecimal(s) => match s.parse::<f64>() {
+                Ok(v) => visitor.visit_f64(v),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&s), &visitor)),
+            },
+            v => Err(de::Error::invalid_type(v.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        match self {
+            Value::Nil => visitor.visit_none(),
+            Value::Array(data) => {
+                let mut values = Param::from(*data);
+                match values.len() {
+                    0 => visitor.visit_none(),
+                    1 => visitor.visit_some(values.remove(0)),
+                    n => Err(de::Error::invalid_length(
+                        n,
+                        &"an array of zero or one element",
+                    )),
+                }
+            }
+            // Be lenient with servers that send the value as is, without the array wrapper.
+            v => visitor.visit_some(v),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        match self {
+            Value::Nil => visitor.visit_unit(),
+            Value::Struct { ref member } if member.is_empty() => visitor.visit_unit(),
+            v => Err(de::Error::invalid_type(v.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> XmlResult<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> XmlResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> XmlResult<V::Value> {
+        match self {
+            Value::Struct { mut member } if member.len() == 1 => {
+                let (variant, value) = member.pop().unwrap().into_pair();
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            // A bare string names a unit variant.
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            v => Err(de::Error::invalid_type(v.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf seq tuple
+        tuple_struct map struct identifier
+    }
+}
+
+impl Value {
+    // Integers that do not fit in an `i32` travel as strings, so accept both.
+    fn deserialize_integer<'de, V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        match self {
+            Value::I4(v) | Value::Int(v) => visitor.visit_i32(v),
+            Value::I8(v) => visitor.visit_i64(v),
+            Value::I1(v) => visitor.visit_i8(v),
+            Value::I2(v) => visitor.visit_i16(v),
+            Value::String(s) => {
+                let text = s.trim();
+                if text.starts_with('-') {
+                    match text.parse::<i64>() {
+                        Ok(v) => visitor.visit_i64(v),
+                        Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&s), &visitor)),
+                    }
+                } else {
+                    match text.parse::<u64>() {
+                        Ok(v) => visitor.visit_u64(v),
+                        Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&s), &visitor)),
+                    }
+                }
+            }
+            v => Err(de::Error::invalid_type(v.unexpected(), &visitor)),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, XmlError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+fn visit_array<'de, V: Visitor<'de>>(values: Vec<Value>, visitor: V) -> XmlResult<V::Value> {
+    let len = values.len();
+    let mut deserializer = SeqDeserializer {
+        iter: values.into_iter(),
+    };
+    let seq = visitor.visit_seq(&mut deserializer)?;
+    match deserializer.iter.len() {
+        0 => Ok(seq),
+        _ => Err(de::Error::invalid_length(len, &"fewer elements in array")),
+    }
+}
+
+fn visit_struct<'de, V: Visitor<'de>>(members: Vec<Member>, visitor: V) -> XmlResult<V::Value> {
+    let mut deserializer = MapDeserializer {
+        iter: members.into_iter(),
+        value: None,
+    };
+    visitor.visit_map(&mut deserializer)
+}
+
+struct SeqDeserializer {
+    iter: vec::IntoIter<Value>,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = XmlError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> XmlResult<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer {
+    iter: vec::IntoIter<Member>,
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = XmlError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> XmlResult<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(member) => {
+                let (name, value) = member.into_pair();
+                self.value = Some(value);
+                seed.deserialize(Value::String(name)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> XmlResult<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = XmlError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> XmlResult<(V::Value, VariantDeserializer)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(Value::String(self.variant))?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Value,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = XmlError;
+
+    fn unit_variant(self) -> XmlResult<()> {
+        de::Deserialize::deserialize(self.value)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> XmlResult<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.value)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> XmlResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.value.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> XmlResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.value.deserialize_map(visitor)
+    }
+}
+
+/*
+    Deserializer over the positional params of a call or response.
+    This is the reverse of `to_params`: sequences and tuples are read from all params,
+    anything else is read from the only param there is.
+*/
+pub struct ParamsDeserializer {
+    params: Params,
+}
+
+impl ParamsDeserializer {
+    pub fn new(params: Params) -> Self {
+        Self { params }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ParamsDeserializer {
+    type Error = XmlError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_any(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        visit_array(self.params.into(), visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> XmlResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> XmlResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_option(visitor)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_unit(visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> XmlResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_i8(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_i16(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_i32(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_i64(visitor)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_u8(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_u16(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_u32(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_u64(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_f32(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        Value::from(self.params).deserialize_f64(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> XmlResult<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf map struct identifier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xmlfmt::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: i32,
+        y: u64,
+        label: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Line(i8, i8),
+        Rect { w: u16, h: u16 },
+    }
+
+    fn round_trip<T>(value: T)
+    where
+        T: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let data = to_value(&value).unwrap();
+        assert_eq!(from_value::<T>(data).unwrap(), value);
+    }
+
+    #[test]
+    fn reads_pod_values() {
+        round_trip(true);
+        round_trip(-12i8);
+        round_trip(40_000u16);
+        round_trip(i32::MIN);
+        round_trip(i64::MIN);
+        round_trip(u32::MAX);
+        round_trip(u64::MAX);
+        round_trip(-1.25f64);
+        round_trip('c');
+        round_trip(String::from("South Dakota"));
+        round_trip(());
+    }
+
+    #[test]
+    fn reads_integers_from_strings() {
+        assert_eq!(from_value::<u64>(Value::String("42".into())).unwrap(), 42);
+        assert_eq!(from_value::<i32>(Value::String("-7".into())).unwrap(), -7);
+        assert!(from_value::<u64>(Value::String("-7".into())).is_err());
+        assert!(from_value::<u64>(Value::String("seven".into())).is_err());
+        assert!(from_value::<u8>(Value::Int(300)).is_err());
+    }
+
+    #[test]
+    fn reads_extension_values() {
+        assert_eq!(
+            from_value::<i64>(Value::I8(-5_000_000_000)).unwrap(),
+            -5_000_000_000
+        );
+        assert_eq!(from_value::<u8>(Value::I1(7)).unwrap(), 7);
+        assert_eq!(from_value::<i16>(Value::I2(-300)).unwrap(), -300);
+        assert_eq!(from_value::<f32>(Value::Float(0.5)).unwrap(), 0.5);
+        assert_eq!(
+            from_value::<f64>(Value::BigDecimal("1.25".into())).unwrap(),
+            1.25
+        );
+        assert_eq!(
+            from_value::<String>(Value::BigDecimal("1.25".into())).unwrap(),
+            "1.25"
+        );
+        assert!(from_value::<i32>(Value::I8(1 << 40)).is_err());
+    }
+
+    #[test]
+    fn reads_options() {
+        round_trip(None::<i32>);
+        round_trip(Some(5));
+        assert_eq!(from_value::<Option<i32>>(Value::Nil).unwrap(), None);
+        assert_eq!(from_value::<Option<i32>>(Value::Int(3)).unwrap(), Some(3));
+        let too_long = Value::to_array(vec![Value::Int(1), Value::Int(2)]);
+        assert!(from_value::<Option<i32>>(too_long).is_err());
+    }
+
+    #[test]
+    fn reads_collections() {
+        round_trip(vec![1, 2, 3]);
+        round_trip((1, String::from("x"), false));
+        let mut map = HashMap::new();
+        map.insert(String::from("a"), 1);
+        map.insert(String::from("b"), 2);
+        round_trip(map);
+    }
+
+    #[test]
+    fn reads_structs() {
+        round_trip(Point {
+            x: 1,
+            y: u64::MAX,
+            label: Some("origin".into()),
+        });
+
+        let missing_field = Value::to_struct(vec![Member::new("x", Value::Int(1))]);
+        assert!(from_value::<Point>(missing_field).is_err());
+    }
+
+    #[test]
+    fn reads_enum_variants() {
+        round_trip(Shape::Empty);
+        round_trip(Shape::Circle(2.0));
+        round_trip(Shape::Line(1, -1));
+        round_trip(Shape::Rect { w: 3, h: 4 });
+        assert_eq!(
+            from_value::<Shape>(Value::String("Empty".into())).unwrap(),
+            Shape::Empty
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_types() {
+        assert!(from_value::<i32>(Value::Bool(true)).is_err());
+        assert!(from_value::<Vec<i32>>(Value::Int(1)).is_err());
+        assert!(from_value::<(i32, i32)>(Value::to_array(vec![Value::Int(1)])).is_err());
+    }
+
+    #[test]
+    fn reads_params() {
+        let params = to_params(&(1, "x")).unwrap();
+        assert_eq!(
+            from_params::<(i32, String)>(params).unwrap(),
+            (1, "x".into())
+        );
+
+        let params = Params::new(vec![Value::Int(42)]);
+        assert_eq!(from_params::<i32>(params).unwrap(), 42);
+
+        let params = Params::new(vec![Value::Int(42)]);
+        assert_eq!(from_params::<(i32,)>(params).unwrap(), (42,));
+
+        assert_eq!(
+            from_params::<Vec<i32>>(Params::default()).unwrap(),
+            Vec::<i32>::new()
+        );
+        from_params::<()>(Params::default()).unwrap();
+
+        let point = Point {
+            x: 1,
+            y: 2,
+            label: None,
+        };
+        let params = to_params(&point).unwrap();
+        assert_eq!(from_params::<Point>(params).unwrap(), point);
+    }
+}
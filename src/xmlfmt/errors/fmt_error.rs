@@ -6,6 +6,12 @@ pub enum FmtError {
     Decoding(String),
     Encoding(String),
     UnsupportedFormat(String),
+    // The document is not well-formed XML.
+    MalformedXml(String),
+    // The document declares an encoding other than UTF-8.
+    UnsupportedEncoding(String),
+    // The document contains bytes or characters not allowed in its encoding.
+    InvalidCharacter(String),
 }
 
 impl fmt::Display for FmtError {
@@ -14,6 +20,13 @@ impl fmt::Display for FmtError {
             FmtError::Decoding(t) => write!(f, "Issue while decoding data structure: {}", t),
             FmtError::Encoding(t) => write!(f, "Issue while encoding data structure: {}", t),
             FmtError::UnsupportedFormat(t) => write!(f, "Given structure is not supported: {}", t),
+            FmtError::MalformedXml(t) => write!(f, "Document is not well-formed XML: {}", t),
+            FmtError::UnsupportedEncoding(t) => {
+                write!(f, "Document encoding is not supported: {}", t)
+            }
+            FmtError::InvalidCharacter(t) => {
+                write!(f, "Document contains an invalid character: {}", t)
+            }
         }
     }
 }
@@ -1,4 +1,4 @@
-use crate::xmlfmt::FmtError;
+use crate::xmlfmt::{Fault, FaultCode, FmtError};
 use serde::{Deserialize, Serialize, de, ser};
 use std::fmt;
 
@@ -8,6 +8,20 @@ pub enum XmlError {
     Format(FmtError),
     Server(String), // TODO: Handle errors from tiny_http instead
     Http(String), // TODO: force type to ureq::Error, but had issue. Replace to see compiler errors.
+    // Fault returned by the remote method.
+    Fault(Fault),
+    // Fault using one of the interoperability codes, such as an unknown method or invalid params.
+    Standard(FaultCode, Fault),
+}
+
+// Faults with an interoperability code become `XmlError::Standard`, others `XmlError::Fault`.
+impl From<Fault> for XmlError {
+    fn from(fault: Fault) -> Self {
+        match fault.standard_code() {
+            Some(fault_code) => XmlError::Standard(fault_code, fault),
+            None => XmlError::Fault(fault),
+        }
+    }
 }
 
 impl std::error::Error for XmlError {}
@@ -19,6 +33,7 @@ impl fmt::Display for XmlError {
             XmlError::Format(e) => write!(f, "Format error: {}", e),
             XmlError::Http(t) => write!(f, "HTTP error: {}", t),
             XmlError::Server(s) => write!(f, "Server error: {}", s),
+            XmlError::Fault(fault) | XmlError::Standard(_, fault) => write!(f, "{}", fault),
         }
     }
 }
@@ -0,0 +1,110 @@
+use crate::xmlfmt::{FmtError, Value, XmlError, from_value};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/*
+    Fault returned by a method, written on the wire as
+    <fault>
+        <value>
+            <struct>
+                <member><name>faultCode</name><value><int>4</int></value></member>
+                <member><name>faultString</name><value><string>Too many parameters.</string></value></member>
+            </struct>
+        </value>
+    </fault>
+    Other members are ignored when reading, as some implementations add their own.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fault {
+    #[serde(rename = "faultCode")]
+    pub code: i32,
+    #[serde(rename = "faultString")]
+    pub message: String,
+}
+
+impl Fault {
+    pub fn new<T>(code: i32, message: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// The `faultCode` member.
+    pub fn fault_code(&self) -> i32 {
+        self.code
+    }
+
+    /// The `faultString` member.
+    pub fn fault_string(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fault {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for Fault {}
+
+impl From<Fault> for Value {
+    fn from(fault: Fault) -> Self {
+        Value::fault(fault.code, fault.message)
+    }
+}
+
+// Fails unless the value is a struct with an integer `faultCode` and a string `faultString`.
+impl TryFrom<Value> for Fault {
+    type Error = XmlError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        from_value(value)
+            .map_err(|e| XmlError::Format(FmtError::Decoding(format!("malformed fault: {}", e))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xmlfmt::Member;
+
+    #[test]
+    fn converts_to_and_from_value() {
+        let fault = Fault::new(4, "Too many parameters");
+        let value = Value::from(fault.clone());
+        assert_eq!(value, Value::fault(4, "Too many parameters"));
+        assert_eq!(Fault::try_from(value).unwrap(), fault);
+        assert_eq!(fault.fault_code(), 4);
+        assert_eq!(fault.fault_string(), "Too many parameters");
+    }
+
+    #[test]
+    fn reads_fault_with_extra_members() {
+        let value = Value::to_struct(vec![
+            Member::new("faultString", Value::String("boom".into())),
+            Member::new("faultCode", Value::I4(-1)),
+            Member::new("traceback", Value::String("...".into())),
+        ]);
+        assert_eq!(Fault::try_from(value).unwrap(), Fault::new(-1, "boom"));
+    }
+
+    #[test]
+    fn rejects_malformed_fault() {
+        assert!(Fault::try_from(Value::Int(4)).is_err());
+
+        let missing_string = Value::to_struct(vec![Member::new("faultCode", Value::Int(4))]);
+        assert!(Fault::try_from(missing_string).is_err());
+
+        let wrong_type = Value::to_struct(vec![
+            Member::new("faultCode", Value::Bool(true)),
+            Member::new("faultString", Value::String("boom".into())),
+        ]);
+        let error = Fault::try_from(wrong_type).unwrap_err().to_string();
+        assert!(error.contains("malformed fault"), "{}", error);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::xmlfmt::Fault;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/*
+    Fault codes from the "Specification for Fault Code Interoperability"
+    (http://xmlrpc-epi.sourceforge.net/specs/rfc.fault_codes.php), used by Python, Apache and libxmlrpc.
+    The server answers with these when a call fails before or around the handler,
+    and the client reports faults carrying them as `XmlError::Standard`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FaultCode {
+    // The request is not well-formed XML.
+    ParseError,
+    UnsupportedEncoding,
+    InvalidCharacter,
+    // The request is XML, but not a valid XML-RPC call.
+    InvalidRequest,
+    MethodNotFound,
+    InvalidParams,
+    InternalError,
+    ApplicationError,
+    SystemError,
+    TransportError,
+}
+
+impl FaultCode {
+    pub fn code(self) -> i32 {
+        match self {
+            FaultCode::ParseError => -32700,
+            FaultCode::UnsupportedEncoding => -32701,
+            FaultCode::InvalidCharacter => -32702,
+            FaultCode::InvalidRequest => -32600,
+            FaultCode::MethodNotFound => -32601,
+            FaultCode::InvalidParams => -32602,
+            FaultCode::InternalError => -32603,
+            FaultCode::ApplicationError => -32500,
+            FaultCode::SystemError => -32400,
+            FaultCode::TransportError => -32300,
+        }
+    }
+
+    pub fn from_code(code: i32) -> Option<FaultCode> {
+        let fault_code = match code {
+            -32700 => FaultCode::ParseError,
+            -32701 => FaultCode::UnsupportedEncoding,
+            -32702 => FaultCode::InvalidCharacter,
+            -32600 => FaultCode::InvalidRequest,
+            -32601 => FaultCode::MethodNotFound,
+            -32602 => FaultCode::InvalidParams,
+            -32603 => FaultCode::InternalError,
+            -32500 => FaultCode::ApplicationError,
+            -32400 => FaultCode::SystemError,
+            -32300 => FaultCode::TransportError,
+            _ => return None,
+        };
+        Some(fault_code)
+    }
+
+    /// Build a fault with this code, and a message made of the standard text followed by `detail`.
+    pub fn fault<T: fmt::Display>(self, detail: T) -> Fault {
+        Fault::new(self.code(), format!("{}: {}", self, detail))
+    }
+}
+
+// The standard text of each code, as given by the spec.
+impl fmt::Display for FaultCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            FaultCode::ParseError => "parse error. not well formed",
+            FaultCode::UnsupportedEncoding => "parse error. unsupported encoding",
+            FaultCode::InvalidCharacter => "parse error. invalid character for encoding",
+            FaultCode::InvalidRequest => "server error. invalid xml-rpc. not conforming to spec",
+            FaultCode::MethodNotFound => "server error. requested method not found",
+            FaultCode::InvalidParams => "server error. invalid method parameters",
+            FaultCode::InternalError => "server error. internal xml-rpc error",
+            FaultCode::ApplicationError => "application error",
+            FaultCode::SystemError => "system error",
+            FaultCode::TransportError => "transport error",
+        };
+        f.write_str(text)
+    }
+}
+
+impl From<FaultCode> for i32 {
+    fn from(fault_code: FaultCode) -> Self {
+        fault_code.code()
+    }
+}
+
+impl Fault {
+    /// The interoperability code of this fault, if it uses one.
+    pub fn standard_code(&self) -> Option<FaultCode> {
+        FaultCode::from_code(self.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_codes_both_ways() {
+        for fault_code in [
+            FaultCode::ParseError,
+            FaultCode::UnsupportedEncoding,
+            FaultCode::InvalidCharacter,
+            FaultCode::InvalidRequest,
+            FaultCode::MethodNotFound,
+            FaultCode::InvalidParams,
+            FaultCode::InternalError,
+            FaultCode::ApplicationError,
+            FaultCode::SystemError,
+            FaultCode::TransportError,
+        ] {
+            assert_eq!(FaultCode::from_code(fault_code.code()), Some(fault_code));
+        }
+        assert_eq!(FaultCode::from_code(4), None);
+        assert_eq!(i32::from(FaultCode::MethodNotFound), -32601);
+    }
+
+    #[test]
+    fn builds_fault_with_standard_text() {
+        let fault = FaultCode::MethodNotFound.fault("add");
+        assert_eq!(fault.code, -32601);
+        assert_eq!(
+            fault.message,
+            "server error. requested method not found: add"
+        );
+        assert_eq!(fault.standard_code(), Some(FaultCode::MethodNotFound));
+        assert_eq!(Fault::new(4, "boom").standard_code(), None);
+    }
+}
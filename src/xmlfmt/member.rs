@@ -36,6 +36,18 @@ impl Member {
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn into_pair(self) -> (String, Value) {
+        (self.name, self.value)
+    }
+
     pub fn from_hashmap(hashmap: HashMap<String, Value>) -> Vec<Member> {
         hashmap.iter().fold(
             Vec::with_capacity(hashmap.capacity()),
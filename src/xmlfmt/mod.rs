@@ -1,31 +1,68 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use std::result::Result;
 
+mod binary; // base64 text
 mod call;
 pub mod data;
+pub mod datetime; // dateTime.iso8601
+pub mod de; // Value -> serde
 pub mod errors; // rust errors (local)
+pub mod fault;
+pub mod fault_code; // interoperability fault codes
 pub mod member;
 pub mod params; // method response/call
+mod parser; // wire decoder
 pub mod response;
+pub mod ser; // serde -> Value
 pub mod value; // value type
+mod writer; // wire encoder
 
 pub(crate) mod to_xml; // may not be needed?
 
+pub use self::datetime::XmlDateTime;
 pub use self::errors::{FmtError, XmlError};
+pub use self::fault::Fault;
+pub use self::fault_code::FaultCode;
 pub use self::response::{MethodResponse, XmlResponse};
 pub use crate::xmlfmt::params::{Param, Params};
 pub use crate::xmlfmt::value::Value;
-use self::{data::Data, member::Member};
+pub use self::member::Member;
+use self::data::Data;
 pub(crate) use self::to_xml::ToXml;
 
 // interface point for making http request
 pub(crate) use self::call::Call;
+pub(crate) use self::parser::{parse_call, parse_response};
 
 pub type XmlResult<T> = Result<T, XmlError>;
 
-// pub fn into_params<T: Serialize>(v: &T) -> XmlResult<Params> {
-//     let content = match v.serialize(ser::Serializer {})? {
-//         Value::Array(param) => Into::into(*param),
-//         value => vec![value],
-//     };
-//     Ok(Params::new(content))
-// }
+/// Convert any serializable type into a `Value`, following the mapping in `xmlfmt/README.md`.
+pub fn to_value<T: Serialize + ?Sized>(v: &T) -> XmlResult<Value> {
+    v.serialize(ser::Serializer)
+}
+
+/// Convert any serializable type into `Params`.
+/// Sequences and tuples are spread into positional params, `()` and unit structs become no params,
+/// anything else becomes a single param.
+pub fn to_params<T: Serialize + ?Sized>(v: &T) -> XmlResult<Params> {
+    if ser::is_unit(v) {
+        return Ok(Params::new(Vec::new()));
+    }
+    let content = match to_value(v)? {
+        Value::Array(param) => (*param).into(),
+        value => vec![value],
+    };
+    Ok(Params::new(content))
+}
+
+/// Read any deserializable type out of a `Value`, following the mapping in `xmlfmt/README.md`.
+pub fn from_value<T: DeserializeOwned>(v: Value) -> XmlResult<T> {
+    T::deserialize(v)
+}
+
+/// Read any deserializable type out of `Params`.
+/// Sequences and tuples are read from all positional params, anything else from the only param.
+pub fn from_params<T: DeserializeOwned>(v: Params) -> XmlResult<T> {
+    T::deserialize(de::ParamsDeserializer::new(v))
+}
@@ -5,7 +5,7 @@ pub type Param = Vec<Value>;
 
 // Params is a list of param, containing value - only ever used in methodResponse and methodCall
 #[serde_with::skip_serializing_none]
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Serialize, Deserialize)]
 pub struct Params {
     param: Option<Param>,
 }
@@ -14,11 +14,22 @@ impl Params {
     pub fn new(param: Param) -> Self {
         Self { param: Some(param) }
     }
+
+    pub fn values(&self) -> &[Value] {
+        self.param.as_deref().unwrap_or_default()
+    }
+}
+
+// `<params/>` and `<params></params>` both mean "no params", however `param` was filled in.
+impl PartialEq for Params {
+    fn eq(&self, other: &Self) -> bool {
+        self.values() == other.values()
+    }
 }
 
-impl Into<Value> for Params {
-    fn into(self) -> Value {
-        match self.param {
+impl From<Params> for Value {
+    fn from(params: Params) -> Self {
+        match params.param {
             Some(mut param) => match param.len(){
                 0 => Value::Nil,
                 1 => param.pop().unwrap(),
@@ -29,14 +40,14 @@ impl Into<Value> for Params {
     }
 }
 
-impl Into<Param> for Params {
-    fn into(self) -> Param {
-        self.param.unwrap_or_default()
+impl From<Params> for Param {
+    fn from(params: Params) -> Self {
+        params.param.unwrap_or_default()
     }
 }
 
-impl Into<MethodResponse> for Params {
-    fn into(self) -> MethodResponse {
-        MethodResponse::Params(self)
+impl From<Params> for MethodResponse {
+    fn from(params: Params) -> Self {
+        MethodResponse::Params(params)
     }
 }
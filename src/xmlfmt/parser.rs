@@ -0,0 +1,711 @@
+use crate::xmlfmt::binary::decode_base64;
+use crate::xmlfmt::{
+    Call, Fault, FmtError, Member, MethodResponse, Params, Value, XmlDateTime, XmlError, XmlResult,
+};
+use std::io::Read;
+use xml::common::Position;
+use xml::reader::{ErrorKind, EventReader, ParserConfig, XmlEvent};
+
+/*
+    Pull parser for XML-RPC documents, built on top of the `xml` event reader.
+    It accepts what the spec and the major implementations (Python, Apache, libxmlrpc) produce:
+    - whitespace, comments and processing instructions between elements
+    - `<value>text</value>` without a type element, read as an implicit string
+    - `<boolean>` written as `0`/`1` (or `true`/`false`)
+    - `<params>` omitted or empty, and empty elements such as `<string/>` or `<data/>`
+    With `extensions`, it also reads the Apache/Python extension types, with or without the `ex:` prefix:
+    `<i8>`, `<ex:i1>`, `<ex:i2>`, `<ex:float>` and `<ex:bigdecimal>`.
+    Values are read recursively, so they may nest at most `MAX_DEPTH` levels deep:
+    a few kilobytes of nested arrays would otherwise overflow the stack.
+*/
+pub(crate) fn parse_call<R: Read>(source: R, extensions: bool) -> XmlResult<Call> {
+    let mut parser = Parser::new(source, extensions);
+    parser.expect_start("methodCall")?;
+    parser.expect_start("methodName")?;
+    let name = parser.read_text("methodName")?.trim().to_owned();
+    let params = match parser.next_tag()? {
+        Tag::Start(tag) if tag == "params" => {
+            let params = parser.parse_params()?;
+            parser.expect_end("methodCall")?;
+            params
+        }
+        Tag::End(tag) if tag == "methodCall" => Params::default(),
+        tag => return Err(parser.unexpected(tag, "<params> or </methodCall>")),
+    };
+    parser.expect_document_end()?;
+    Ok(Call::new(name, params))
+}
+
+pub(crate) fn parse_response<R: Read>(source: R, extensions: bool) -> XmlResult<MethodResponse> {
+    let mut parser = Parser::new(source, extensions);
+    parser.expect_start("methodResponse")?;
+    let response = match parser.next_tag()? {
+        Tag::Start(tag) if tag == "params" => MethodResponse::Params(parser.parse_params()?),
+        Tag::Start(tag) if tag == "fault" => {
+            parser.expect_start("value")?;
+            let value = parser.parse_value()?;
+            let fault = Fault::try_from(value)?;
+            parser.expect_end("fault")?;
+            MethodResponse::Fault(fault)
+        }
+        tag => return Err(parser.unexpected(tag, "<params> or <fault>")),
+    };
+    parser.expect_end("methodResponse")?;
+    parser.expect_document_end()?;
+    Ok(response)
+}
+
+// Structural events, once text between elements has been dealt with.
+#[derive(Debug)]
+enum Tag {
+    Start(String),
+    End(String),
+    Eof,
+}
+
+enum Event {
+    Tag(Tag),
+    Text(String),
+}
+
+const EXTENSION_TYPES: [&str; 5] = ["i8", "i1", "i2", "float", "bigdecimal"];
+
+// Most `<value>` elements open at once, a param or the fault being the first level.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<R: Read> {
+    reader: EventReader<R>,
+    extensions: bool,
+    // `<value>` elements currently open.
+    depth: usize,
+}
+
+impl<R: Read> Parser<R> {
+    fn new(source: R, extensions: bool) -> Self {
+        let config = ParserConfig::new()
+            .whitespace_to_characters(true)
+            .cdata_to_characters(true)
+            .ignore_comments(true)
+            .coalesce_characters(true);
+        Self {
+            reader: EventReader::new_with_config(source, config),
+            extensions,
+            depth: 0,
+        }
+    }
+
+    fn error<T: std::fmt::Display>(&self, message: T) -> XmlError {
+        XmlError::Format(FmtError::Decoding(format!(
+            "{} at {}",
+            message,
+            self.reader.position()
+        )))
+    }
+
+    fn unexpected(&self, tag: Tag, expected: &str) -> XmlError {
+        let found = match tag {
+            Tag::Start(name) => format!("<{}>", name),
+            Tag::End(name) => format!("</{}>", name),
+            Tag::Eof => "end of document".to_owned(),
+        };
+        self.error(format!("expected {}, found {}", expected, found))
+    }
+
+    fn next_event(&mut self) -> XmlResult<Event> {
+        loop {
+            let event = self.reader.next().map_err(malformed)?;
+            return Ok(match event {
+                XmlEvent::StartElement { name, .. } => Event::Tag(Tag::Start(name.local_name)),
+                XmlEvent::EndElement { name } => Event::Tag(Tag::End(name.local_name)),
+                XmlEvent::EndDocument => Event::Tag(Tag::Eof),
+                XmlEvent::Characters(text) | XmlEvent::Whitespace(text) | XmlEvent::CData(text) => {
+                    Event::Text(text)
+                }
+                // prolog, doctype, comments and processing instructions carry nothing for us
+                _ => continue,
+            });
+        }
+    }
+
+    // Next element boundary, skipping whitespace that is only there for indentation.
+    fn next_tag(&mut self) -> XmlResult<Tag> {
+        loop {
+            match self.next_event()? {
+                Event::Tag(tag) => return Ok(tag),
+                Event::Text(text) if text.trim().is_empty() => continue,
+                Event::Text(text) => {
+                    return Err(self.error(format!("unexpected text {:?}", text.trim())));
+                }
+            }
+        }
+    }
+
+    fn expect_start(&mut self, name: &str) -> XmlResult<()> {
+        match self.next_tag()? {
+            Tag::Start(tag) if tag == name => Ok(()),
+            tag => Err(self.unexpected(tag, &format!("<{}>", name))),
+        }
+    }
+
+    fn expect_end(&mut self, name: &str) -> XmlResult<()> {
+        match self.next_tag()? {
+            Tag::End(tag) if tag == name => Ok(()),
+            tag => Err(self.unexpected(tag, &format!("</{}>", name))),
+        }
+    }
+
+    fn expect_document_end(&mut self) -> XmlResult<()> {
+        match self.next_tag()? {
+            Tag::Eof => Ok(()),
+            tag => Err(self.unexpected(tag, "end of document")),
+        }
+    }
+
+    // Text content of a leaf element, up to and including its end tag.
+    fn read_text(&mut self, name: &str) -> XmlResult<String> {
+        let mut text = String::new();
+        loop {
+            match self.next_event()? {
+                Event::Text(t) => text.push_str(&t),
+                Event::Tag(Tag::End(tag)) if tag == name => return Ok(text),
+                Event::Tag(tag) => return Err(self.unexpected(tag, &format!("</{}>", name))),
+            }
+        }
+    }
+
+    // Content of <params>, up to and including </params>.
+    fn parse_params(&mut self) -> XmlResult<Params> {
+        let mut values = Vec::new();
+        loop {
+            match self.next_tag()? {
+                Tag::Start(tag) if tag == "param" => {
+                    self.expect_start("value")?;
+                    values.push(self.parse_value()?);
+                    self.expect_end("param")?;
+                }
+                Tag::End(tag) if tag == "params" => return Ok(Params::new(values)),
+                tag => return Err(self.unexpected(tag, "<param> or </params>")),
+            }
+        }
+    }
+
+    // Content of <value>, up to and including </value>.
+    fn parse_value(&mut self) -> XmlResult<Value> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!(
+                "values nested more than {} levels deep",
+                MAX_DEPTH
+            )));
+        }
+        self.depth += 1;
+        let value = self.parse_value_content();
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_value_content(&mut self) -> XmlResult<Value> {
+        let mut text = String::new();
+        loop {
+            match self.next_event()? {
+                Event::Text(t) => text.push_str(&t),
+                // A value without a type element is a string.
+                Event::Tag(Tag::End(tag)) if tag == "value" => return Ok(Value::String(text)),
+                Event::Tag(Tag::Start(tag)) => {
+                    if !text.trim().is_empty() {
+                        return Err(self.error(format!(
+                            "unexpected text {:?} before <{}>",
+                            text.trim(),
+                            tag
+                        )));
+                    }
+                    let value = self.parse_typed_value(&tag)?;
+                    self.expect_end("value")?;
+                    return Ok(value);
+                }
+                Event::Tag(tag) => return Err(self.unexpected(tag, "a value")),
+            }
+        }
+    }
+
+    // Content of a type element such as <int>, up to and including its end tag.
+    fn parse_typed_value(&mut self, tag: &str) -> XmlResult<Value> {
+        if !self.extensions && EXTENSION_TYPES.contains(&tag) {
+            return Err(self.error(format!(
+                "<{}> is an extension type, and extensions are not enabled",
+                tag
+            )));
+        }
+        let value = match tag {
+            "i4" => Value::I4(self.parse_number(tag)?),
+            "int" => Value::Int(self.parse_number(tag)?),
+            "boolean" => {
+                let text = self.read_text(tag)?;
+                match text.trim() {
+                    "1" | "true" => Value::Bool(true),
+                    "0" | "false" => Value::Bool(false),
+                    other => return Err(self.error(format!("invalid boolean {:?}", other))),
+                }
+            }
+            "string" => Value::String(self.read_text(tag)?),
+            "double" => Value::Double(self.parse_number(tag)?),
+            "dateTime.iso8601" => {
+                let text = self.read_text(tag)?;
+                Value::DateTime(XmlDateTime::parse_str(text.trim()).map_err(|e| self.error(e))?)
+            }
+            "base64" => {
+                let text = self.read_text(tag)?;
+                Value::Base64(decode_base64(&text).map_err(|e| self.error(e))?)
+            }
+            "array" => {
+                let values = match self.next_tag()? {
+                    Tag::Start(data) if data == "data" => self.parse_array_data()?,
+                    // Some implementations write an empty array as <array/>.
+                    Tag::End(end) if end == "array" => return Ok(Value::to_array(Vec::new())),
+                    other => return Err(self.unexpected(other, "<data>")),
+                };
+                self.expect_end("array")?;
+                Value::to_array(values)
+            }
+            "struct" => Value::to_struct(self.parse_struct_members()?),
+            "nil" => {
+                self.expect_end(tag)?;
+                Value::Nil
+            }
+            "i8" => Value::I8(self.parse_number(tag)?),
+            "i1" => Value::I1(self.parse_number(tag)?),
+            "i2" => Value::I2(self.parse_number(tag)?),
+            "float" => Value::Float(self.parse_number(tag)?),
+            "bigdecimal" => {
+                let text = self.read_text(tag)?;
+                if !is_decimal(text.trim()) {
+                    return Err(self.error(format!("invalid <bigdecimal> {:?}", text.trim())));
+                }
+                Value::BigDecimal(text.trim().to_owned())
+            }
+            other => {
+                return Err(self.error(format!("unsupported value type <{}>", other)));
+            }
+        };
+        Ok(value)
+    }
+
+    fn parse_number<T>(&mut self, tag: &str) -> XmlResult<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let text = self.read_text(tag)?;
+        text.trim()
+            .parse::<T>()
+            .map_err(|e| self.error(format!("invalid <{}> {:?}: {}", tag, text.trim(), e)))
+    }
+
+    // Content of <data>, up to and including </data>.
+    fn parse_array_data(&mut self) -> XmlResult<Vec<Value>> {
+        let mut values = Vec::new();
+        loop {
+            match self.next_tag()? {
+                Tag::Start(tag) if tag == "value" => values.push(self.parse_value()?),
+                Tag::End(tag) if tag == "data" => return Ok(values),
+                tag => return Err(self.unexpected(tag, "<value> or </data>")),
+            }
+        }
+    }
+
+    // Content of <struct>, up to and including </struct>.
+    fn parse_struct_members(&mut self) -> XmlResult<Vec<Member>> {
+        let mut members = Vec::new();
+        loop {
+            match self.next_tag()? {
+                Tag::Start(tag) if tag == "member" => members.push(self.parse_member()?),
+                Tag::End(tag) if tag == "struct" => return Ok(members),
+                tag => return Err(self.unexpected(tag, "<member> or </struct>")),
+            }
+        }
+    }
+
+    // Content of <member>, up to and including </member>. Name and value may come in any order.
+    fn parse_member(&mut self) -> XmlResult<Member> {
+        let mut name = None;
+        let mut value = None;
+        loop {
+            match self.next_tag()? {
+                Tag::Start(tag) if tag == "name" && name.is_none() => {
+                    name = Some(self.read_text("name")?)
+                }
+                Tag::Start(tag) if tag == "value" && value.is_none() => {
+                    value = Some(self.parse_value()?)
+                }
+                Tag::End(tag) if tag == "member" => break,
+                tag => return Err(self.unexpected(tag, "<name>, <value> or </member>")),
+            }
+        }
+        match (name, value) {
+            (Some(name), Some(value)) => Ok(Member::new(name, value)),
+            (None, _) => Err(self.error("struct member without <name>")),
+            (_, None) => Err(self.error("struct member without <value>")),
+        }
+    }
+}
+
+// The `xml` crate only tells encoding problems apart from other syntax errors by their text.
+fn malformed(e: xml::reader::Error) -> XmlError {
+    let message = e.to_string();
+    XmlError::Format(match e.kind() {
+        ErrorKind::Utf8(_) => FmtError::InvalidCharacter(message),
+        ErrorKind::Syntax(text) if text.starts_with("Unsupported encoding") => {
+            FmtError::UnsupportedEncoding(message)
+        }
+        ErrorKind::Syntax(text) if text.starts_with("Invalid character") => {
+            FmtError::InvalidCharacter(message)
+        }
+        _ => FmtError::MalformedXml(message),
+    })
+}
+
+// Digits with an optional sign, fraction and exponent, as Java's `BigDecimal.toString` writes them.
+fn is_decimal(text: &str) -> bool {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(i) => (&text[..i], Some(&text[i + 1..])),
+        None => (text, None),
+    };
+    let mantissa = mantissa.strip_prefix(['+', '-']).unwrap_or(mantissa);
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let exponent_ok = exponent.is_none_or(|e| {
+        let e = e.strip_prefix(['+', '-']).unwrap_or(e);
+        !e.is_empty() && digits(e)
+    });
+    !(whole.is_empty() && fraction.is_empty()) && digits(whole) && digits(fraction) && exponent_ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_value_str(data: &str) -> XmlResult<Value> {
+        parse_value_with(data, false)
+    }
+
+    fn parse_value_with(data: &str, extensions: bool) -> XmlResult<Value> {
+        let document = format!(
+            "<methodResponse xmlns:ex=\"{}\"><params><param>{}</param></params></methodResponse>",
+            crate::xmlfmt::writer::EXTENSIONS_NAMESPACE,
+            data
+        );
+        match parse_response(document.as_bytes(), extensions)? {
+            MethodResponse::Params(params) => Ok(params.values()[0].clone()),
+            MethodResponse::Fault(_) => panic!("expected params"),
+        }
+    }
+
+    #[test]
+    fn reads_implicit_strings() {
+        assert_eq!(
+            parse_value_str("<value>plain text</value>").unwrap(),
+            Value::String("plain text".into())
+        );
+        assert_eq!(
+            parse_value_str("<value>  padded  </value>").unwrap(),
+            Value::String("  padded  ".into())
+        );
+        assert_eq!(
+            parse_value_str("<value></value>").unwrap(),
+            Value::String("".into())
+        );
+        assert_eq!(
+            parse_value_str("<value><string/></value>").unwrap(),
+            Value::String("".into())
+        );
+    }
+
+    #[test]
+    fn reads_booleans() {
+        assert_eq!(
+            parse_value_str("<value><boolean>1</boolean></value>").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            parse_value_str("<value><boolean> 0 </boolean></value>").unwrap(),
+            Value::Bool(false)
+        );
+        assert!(parse_value_str("<value><boolean>2</boolean></value>").is_err());
+    }
+
+    #[test]
+    fn reads_entities_and_cdata() {
+        assert_eq!(
+            parse_value_str("<value><string>a &lt;&amp;&gt; b<![CDATA[<c>]]></string></value>")
+                .unwrap(),
+            Value::String("a <&> b<c>".into())
+        );
+    }
+
+    #[test]
+    fn reads_indented_python_call() {
+        let data = r#"<?xml version='1.0'?>
+<methodCall>
+<methodName>supervisor.getProcessInfo</methodName>
+<params>
+<param>
+<value><string>worker</string></value>
+</param>
+<param>
+<value><array><data>
+<value><i4>1</i4></value>
+<value><double>-0.5</double></value>
+</data></array></value>
+</param>
+</params>
+</methodCall>
+"#;
+        let call = parse_call(data.as_bytes(), false).unwrap();
+        assert_eq!(call.name, "supervisor.getProcessInfo");
+        assert_eq!(
+            call.params,
+            Params::new(vec![
+                Value::String("worker".into()),
+                Value::to_array(vec![Value::I4(1), Value::Double(-0.5)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn reads_call_without_params() {
+        let call = parse_call(
+            "<methodCall><methodName>ping</methodName></methodCall>".as_bytes(),
+            false,
+        );
+        assert_eq!(call.unwrap(), Call::new("ping", Params::default()));
+    }
+
+    #[test]
+    fn reads_apache_fault() {
+        let data = r#"<?xml version="1.0" encoding="UTF-8"?>
+<methodResponse xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">
+  <!-- generated by Apache XML-RPC -->
+  <fault>
+    <value>
+      <struct>
+        <member>
+          <name>faultCode</name>
+          <value><i4>4</i4></value>
+        </member>
+        <member>
+          <name>faultString</name>
+          <value>Too many parameters</value>
+        </member>
+      </struct>
+    </value>
+  </fault>
+</methodResponse>"#;
+        let response = parse_response(data.as_bytes(), false).unwrap();
+        assert_eq!(
+            response,
+            MethodResponse::Fault(Fault::new(4, "Too many parameters"))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_fault() {
+        let data = concat!(
+            "<methodResponse><fault><value><struct>",
+            "<member><name>faultCode</name><value><int>4</int></value></member>",
+            "</struct></value></fault></methodResponse>",
+        );
+        let error = parse_response(data.as_bytes(), false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("malformed fault"), "{}", error);
+
+        let data = "<methodResponse><fault><value>oops</value></fault></methodResponse>";
+        assert!(parse_response(data.as_bytes(), false).is_err());
+    }
+
+    #[test]
+    fn reads_multiline_base64() {
+        assert_eq!(
+            parse_value_str("<value><base64>\nSGVsbG8s\nIHdvcmxk\n</base64></value>").unwrap(),
+            Value::Base64(b"Hello, world".to_vec())
+        );
+        assert_eq!(
+            parse_value_str("<value><base64/></value>").unwrap(),
+            Value::Base64(vec![])
+        );
+
+        match parse_value_str("<value><base64>SGV*bG8=</base64></value>") {
+            Err(XmlError::Format(FmtError::Decoding(error))) => {
+                assert!(error.contains("'*' at offset 3"), "{}", error)
+            }
+            other => panic!("expected a decoding error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_values_nested_too_deep() {
+        // `depth` values, arrays of one value around an int.
+        let nested = |depth: usize, int: &str| {
+            format!(
+                "{}<value><int>{}</int></value>{}",
+                "<value><array><data>".repeat(depth - 1),
+                int,
+                "</data></array></value>".repeat(depth - 1)
+            )
+        };
+        let mut value = parse_value_str(&nested(MAX_DEPTH, "7")).unwrap();
+        for _ in 1..MAX_DEPTH {
+            value = match value {
+                Value::Array(data) => data.values()[0].clone(),
+                other => panic!("expected an array, got {:?}", other),
+            };
+        }
+        assert_eq!(value, Value::Int(7));
+
+        // Rejected before the innermost value is read, or it would fail as an invalid int.
+        match parse_value_str(&nested(MAX_DEPTH + 1, "oops")) {
+            Err(XmlError::Format(FmtError::Decoding(error))) => {
+                assert!(error.contains("nested more than 128 levels"), "{}", error)
+            }
+            other => panic!("expected a decoding error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_datetimes() {
+        let expected = Value::DateTime(XmlDateTime::new(1998, 7, 17, 14, 8, 55).unwrap());
+        assert_eq!(
+            parse_value_str(
+                "<value><dateTime.iso8601>19980717T14:08:55</dateTime.iso8601></value>"
+            )
+            .unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_value_str(
+                "<value><dateTime.iso8601> 1998-07-17T14:08:55 </dateTime.iso8601></value>"
+            )
+            .unwrap(),
+            expected
+        );
+        assert!(
+            parse_value_str("<value><dateTime.iso8601>yesterday</dateTime.iso8601></value>")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn reads_empty_containers() {
+        assert_eq!(
+            parse_value_str("<value><array><data/></array></value>").unwrap(),
+            Value::to_array(vec![])
+        );
+        assert_eq!(
+            parse_value_str("<value><struct></struct></value>").unwrap(),
+            Value::to_struct(vec![])
+        );
+        assert_eq!(
+            parse_value_str("<value><nil/></value>").unwrap(),
+            Value::Nil
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        assert!(parse_value_str("<value><int>abc</int></value>").is_err());
+        assert!(parse_value_str("<value><unknown>1</unknown></value>").is_err());
+        assert!(parse_value_str("<value>text<int>1</int></value>").is_err());
+        assert!(parse_call("<methodCall><params/></methodCall>".as_bytes(), false).is_err());
+        assert!(parse_response("<methodResponse><params>".as_bytes(), false).is_err());
+        assert!(parse_response("<methodResponse/>".as_bytes(), false).is_err());
+
+        let error = parse_call(
+            "<methodCall>\n<methodName>a</methodName>\n<oops/></methodCall>".as_bytes(),
+            false,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("<oops>"), "{}", error);
+        assert!(error.contains("3:"), "{}", error);
+    }
+
+    #[test]
+    fn reads_extension_types() {
+        assert_eq!(
+            parse_value_with("<value><i8>-5000000000</i8></value>", true).unwrap(),
+            Value::I8(-5_000_000_000)
+        );
+        assert_eq!(
+            parse_value_with("<value><ex:i8>7</ex:i8></value>", true).unwrap(),
+            Value::I8(7)
+        );
+        assert_eq!(
+            parse_value_with("<value><ex:i1>-128</ex:i1></value>", true).unwrap(),
+            Value::I1(-128)
+        );
+        assert_eq!(
+            parse_value_with("<value><ex:i2> 300 </ex:i2></value>", true).unwrap(),
+            Value::I2(300)
+        );
+        assert_eq!(
+            parse_value_with("<value><ex:float>0.25</ex:float></value>", true).unwrap(),
+            Value::Float(0.25)
+        );
+        assert_eq!(
+            parse_value_with(
+                "<value><ex:bigdecimal>-1.50E+3</ex:bigdecimal></value>",
+                true
+            )
+            .unwrap(),
+            Value::BigDecimal("-1.50E+3".into())
+        );
+        assert_eq!(
+            parse_value_with("<value><ex:nil/></value>", true).unwrap(),
+            Value::Nil
+        );
+
+        assert!(parse_value_with("<value><ex:i1>128</ex:i1></value>", true).is_err());
+        assert!(
+            parse_value_with("<value><ex:bigdecimal>1.2.3</ex:bigdecimal></value>", true).is_err()
+        );
+        assert!(
+            parse_value_with("<value><ex:bigdecimal>NaN</ex:bigdecimal></value>", true).is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_extension_types_unless_enabled() {
+        let error = parse_value_str("<value><i8>1</i8></value>")
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("<i8> is an extension type"), "{}", error);
+        assert!(parse_value_str("<value><ex:float>1</ex:float></value>").is_err());
+    }
+
+    #[test]
+    fn tells_encoding_errors_apart() {
+        let error = |data: &[u8]| match parse_call(data, false) {
+            Err(XmlError::Format(e)) => e,
+            other => panic!("expected a format error, got {:?}", other),
+        };
+        assert!(matches!(
+            error(b"<methodCall><methodName>a</methodCall>"),
+            FmtError::MalformedXml(_)
+        ));
+        assert!(matches!(
+            error(b"<?xml version=\"1.0\" encoding=\"EBCDIC\"?><methodCall/>"),
+            FmtError::UnsupportedEncoding(_)
+        ));
+        assert!(matches!(
+            error(b"<methodCall><methodName>a\xff\xfe</methodName></methodCall>"),
+            FmtError::InvalidCharacter(_)
+        ));
+        assert!(matches!(
+            error(b"<methodCall><methodName>&#0;</methodName></methodCall>"),
+            FmtError::InvalidCharacter(_)
+        ));
+        assert!(matches!(
+            error(b"<methodCall><name>a</name></methodCall>"),
+            FmtError::Decoding(_)
+        ));
+    }
+}
@@ -1,28 +1,28 @@
-use crate::xmlfmt::{FmtError, XmlError, XmlResult};
-use crate::{Params, Value, xmlfmt::ToXml};
+use crate::xmlfmt::XmlResult;
+use crate::xmlfmt::parser::parse_response;
+use crate::xmlfmt::writer::write_response;
+use crate::{Fault, Params, xmlfmt::ToXml};
 use serde::{Deserialize, Serialize};
-use serde_xml_rs::to_string;
 
 // used everywhere for type cast declaration
-// TODO: Read the documentation to see if this is supported? Otherwise, I need to handle receiving server response and parse them myself.
-pub type XmlResponse = Result<Params, Value>; // we need to make sure that value for error must be fault!
+pub type XmlResponse = Result<Params, Fault>;
 
 /*
     The design schema for this is this is treated as the root object, so it will alwayas have <?xml ... /> tag.
 
     <?xml version="1.0"?>
     <methodResponse>
-        <params || fault />
+        <params><param><value>{value}</value></param></params>
+        || <fault><value><struct>{faultCode, faultString}</struct></value></fault>
     </methodResponse>
 */
 
-// TODO: For this case here, we need to handle deserializing Tuples as serde_xml_rs does not support it.
 // Only used within this crate, as it's meant to be used to receive response from server
-#[derive(PartialEq, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum MethodResponse {
     Params(Params),
-    Fault(Value),
+    Fault(Fault),
 }
 
 impl MethodResponse {
@@ -31,26 +31,40 @@ impl MethodResponse {
     where
         T: Into<String>,
     {
-        MethodResponse::Fault(Value::fault(code, message))
+        MethodResponse::Fault(Fault::new(code, message))
+    }
+
+    pub fn from_xml(data: &str) -> XmlResult<MethodResponse> {
+        // Reading a document directly accepts every type `Value` can hold, extensions included.
+        parse_response(data.as_bytes(), true)
     }
 
     pub fn new(input: XmlResponse) -> MethodResponse {
         match input {
             Ok(param) => MethodResponse::Params(param),
-            Err(value) => MethodResponse::Fault(value),
+            Err(fault) => MethodResponse::Fault(fault),
         }
     }
 }
 
 impl ToXml for MethodResponse {
     fn to_xml(&self) -> XmlResult<String> {
-        to_string(self).map_err(|e| XmlError::Format(FmtError::Encoding(e.to_string())))
+        write_response(self)
     }
 }
 
-impl Into<MethodResponse> for XmlResponse {
-    fn into(self) -> MethodResponse {
-        match self {
+impl From<MethodResponse> for XmlResponse {
+    fn from(response: MethodResponse) -> Self {
+        match response {
+            MethodResponse::Params(v) => Ok(v),
+            MethodResponse::Fault(e) => Err(e),
+        }
+    }
+}
+
+impl From<XmlResponse> for MethodResponse {
+    fn from(response: XmlResponse) -> Self {
+        match response {
             Ok(v) => MethodResponse::Params(v),
             Err(e) => MethodResponse::Fault(e),
         }
@@ -60,18 +74,19 @@ impl Into<MethodResponse> for XmlResponse {
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
-    use crate::xmlfmt::{Member, ToXml};
+    use crate::xmlfmt::{Member, ToXml, Value};
 
-    // why are we asking for the XmlResult when we expects the params instead?
-    pub fn ser_and_de_response_value(value: MethodResponse) {
+    pub fn ser_and_de_response_value(value: MethodResponse) -> String {
         let data = value.to_xml();
         assert!(
             data.is_ok(),
             "Unable to serialize Response value! {}",
             data.unwrap_err()
         );
-        let result = serde_xml_rs::from_str::<MethodResponse>(&data.unwrap());
+        let data = data.unwrap();
+        let result = MethodResponse::from_xml(&data);
         assert!(result.is_ok_and(|v| v == value));
+        data
     }
 
     #[test]
@@ -88,13 +103,31 @@ pub(crate) mod tests {
         ];
         let params = Params::new(data);
         let response = MethodResponse::Params(params);
-        ser_and_de_response_value(response)
+        assert_eq!(
+            ser_and_de_response_value(response),
+            concat!(
+                r#"<?xml version="1.0"?><methodResponse><params>"#,
+                "<param><value><string>South Dakota</string></value></param>",
+                "<param><value><struct>",
+                "<member><name>foo</name><value><int>42</int></value></member>",
+                "<member><name>bar</name><value><string>baz</string></value></member>",
+                "</struct></value></param>",
+                "</params></methodResponse>",
+            ),
+        )
     }
 
     #[test]
     fn writes_fault() {
-        let fault = Value::fault(4, "Too many parameters".to_owned());
-        let response = MethodResponse::Fault(fault);
-        ser_and_de_response_value(response);
+        let response = MethodResponse::to_fault(4, "Too many parameters");
+        assert_eq!(
+            ser_and_de_response_value(response),
+            concat!(
+                r#"<?xml version="1.0"?><methodResponse><fault><value><struct>"#,
+                "<member><name>faultCode</name><value><int>4</int></value></member>",
+                "<member><name>faultString</name><value><string>Too many parameters</string></value></member>",
+                "</struct></value></fault></methodResponse>",
+            ),
+        );
     }
 }
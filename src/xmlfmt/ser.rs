@@ -0,0 +1,818 @@
+use crate::xmlfmt::datetime::DATETIME_TOKEN;
+use crate::xmlfmt::{FmtError, Member, Value, XmlDateTime, XmlError, XmlResult};
+use serde::ser::{self, Serialize};
+
+/*
+    Serializer that turns any `T: Serialize` into a `Value`.
+    The mapping between serde's data model and XML-RPC types is documented in `xmlfmt/README.md`:
+    - integers that do not fit in an `i32` (`i64`, `u32`, `u64`) are written as `string`
+    - `option` becomes an array with zero or one element
+    - `unit` and `unit_struct` become an empty struct
+    - every enum variant becomes a struct with a single member named after the variant
+*/
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = XmlError;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeTupleVariant;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeStructVariant;
+
+    fn serialize_bool(self, v: bool) -> XmlResult<Value> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> XmlResult<Value> {
+        self.serialize_i32(i32::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> XmlResult<Value> {
+        self.serialize_i32(i32::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> XmlResult<Value> {
+        Ok(Value::Int(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> XmlResult<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_u8(self, v: u8) -> XmlResult<Value> {
+        self.serialize_i32(i32::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> XmlResult<Value> {
+        self.serialize_i32(i32::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> XmlResult<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_u64(self, v: u64) -> XmlResult<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> XmlResult<Value> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> XmlResult<Value> {
+        Ok(Value::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> XmlResult<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> XmlResult<Value> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> XmlResult<Value> {
+        Ok(Value::Base64(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> XmlResult<Value> {
+        Ok(Value::to_array(Vec::new()))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> XmlResult<Value>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Value::to_array(vec![value.serialize(self)?]))
+    }
+
+    fn serialize_unit(self) -> XmlResult<Value> {
+        Ok(Value::to_struct(Vec::new()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> XmlResult<Value> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> XmlResult<Value> {
+        Ok(variant_struct(variant, self.serialize_unit()?))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> XmlResult<Value>
+    where
+        T: ?Sized + Serialize,
+    {
+        match (name, value.serialize(self)?) {
+            (DATETIME_TOKEN, Value::String(text)) => {
+                Ok(Value::DateTime(XmlDateTime::parse(&text)?))
+            }
+            (_, value) => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> XmlResult<Value>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(variant_struct(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> XmlResult<Self::SerializeSeq> {
+        Ok(SerializeVec {
+            vec: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> XmlResult<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> XmlResult<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> XmlResult<Self::SerializeTupleVariant> {
+        Ok(SerializeTupleVariant {
+            name: variant,
+            vec: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> XmlResult<Self::SerializeMap> {
+        Ok(SerializeMap {
+            members: Vec::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> XmlResult<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> XmlResult<Self::SerializeStructVariant> {
+        Ok(SerializeStructVariant {
+            name: variant,
+            members: Vec::with_capacity(len),
+        })
+    }
+}
+
+// Enum variants are written as a struct holding a single member named after the variant.
+fn variant_struct(variant: &str, content: Value) -> Value {
+    Value::to_struct(vec![Member::new(variant, content)])
+}
+
+pub struct SerializeVec {
+    vec: Vec<Value>,
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = Value;
+    type Error = XmlError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.vec.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> XmlResult<Value> {
+        Ok(Value::to_array(self.vec))
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = Value;
+    type Error = XmlError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> XmlResult<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = Value;
+    type Error = XmlError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> XmlResult<Value> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+pub struct SerializeTupleVariant {
+    name: &'static str,
+    vec: Vec<Value>,
+}
+
+impl ser::SerializeTupleVariant for SerializeTupleVariant {
+    type Ok = Value;
+    type Error = XmlError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.vec.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> XmlResult<Value> {
+        Ok(variant_struct(self.name, Value::to_array(self.vec)))
+    }
+}
+
+pub struct SerializeMap {
+    members: Vec<Member>,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = XmlError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.next_key = Some(key.serialize(MapKeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self.next_key.take().ok_or_else(|| {
+            XmlError::Format(FmtError::Encoding(
+                "serialize_value called before serialize_key".into(),
+            ))
+        })?;
+        self.members
+            .push(Member::new(key, value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> XmlResult<Value> {
+        Ok(Value::to_struct(self.members))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Value;
+    type Error = XmlError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.members
+            .push(Member::new(key, value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> XmlResult<Value> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+pub struct SerializeStructVariant {
+    name: &'static str,
+    members: Vec<Member>,
+}
+
+impl ser::SerializeStructVariant for SerializeStructVariant {
+    type Ok = Value;
+    type Error = XmlError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.members
+            .push(Member::new(key, value.serialize(Serializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> XmlResult<Value> {
+        Ok(variant_struct(self.name, Value::to_struct(self.members)))
+    }
+}
+
+// Struct member names in XML-RPC are always strings, so map keys must serialize into one.
+struct MapKeySerializer;
+
+fn key_must_be_a_string() -> XmlError {
+    XmlError::Format(FmtError::UnsupportedFormat(
+        "struct member names must be strings".into(),
+    ))
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = XmlError;
+
+    type SerializeSeq = ser::Impossible<String, XmlError>;
+    type SerializeTuple = ser::Impossible<String, XmlError>;
+    type SerializeTupleStruct = ser::Impossible<String, XmlError>;
+    type SerializeTupleVariant = ser::Impossible<String, XmlError>;
+    type SerializeMap = ser::Impossible<String, XmlError>;
+    type SerializeStruct = ser::Impossible<String, XmlError>;
+    type SerializeStructVariant = ser::Impossible<String, XmlError>;
+
+    fn serialize_bool(self, _v: bool) -> XmlResult<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> XmlResult<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> XmlResult<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_char(self, v: char) -> XmlResult<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> XmlResult<String> {
+        Ok(v.to_owned())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> XmlResult<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> XmlResult<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> XmlResult<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> XmlResult<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> XmlResult<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> XmlResult<String> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> XmlResult<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> XmlResult<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> XmlResult<Self::SerializeSeq> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> XmlResult<Self::SerializeTuple> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeTupleStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeTupleVariant> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> XmlResult<Self::SerializeMap> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeStructVariant> {
+        Err(key_must_be_a_string())
+    }
+}
+
+// Tells `()` and unit structs apart from other values that also serialize into an empty struct,
+// so that `to_params` can send no params at all for them.
+pub(crate) fn is_unit<T: Serialize + ?Sized>(v: &T) -> bool {
+    v.serialize(UnitProbe).is_ok()
+}
+
+struct UnitProbe;
+
+fn not_unit() -> XmlError {
+    XmlError::Format(FmtError::UnsupportedFormat("not a unit".into()))
+}
+
+impl ser::Serializer for UnitProbe {
+    type Ok = ();
+    type Error = XmlError;
+
+    type SerializeSeq = ser::Impossible<(), XmlError>;
+    type SerializeTuple = ser::Impossible<(), XmlError>;
+    type SerializeTupleStruct = ser::Impossible<(), XmlError>;
+    type SerializeTupleVariant = ser::Impossible<(), XmlError>;
+    type SerializeMap = ser::Impossible<(), XmlError>;
+    type SerializeStruct = ser::Impossible<(), XmlError>;
+    type SerializeStructVariant = ser::Impossible<(), XmlError>;
+
+    fn serialize_unit(self) -> XmlResult<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> XmlResult<()> {
+        Ok(())
+    }
+
+    fn serialize_bool(self, _v: bool) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_i8(self, _v: i8) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_i16(self, _v: i16) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_i32(self, _v: i32) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_i64(self, _v: i64) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_u8(self, _v: u8) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_u16(self, _v: u16) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_u32(self, _v: u32) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_u64(self, _v: u64) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_f32(self, _v: f32) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_f64(self, _v: f64) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_char(self, _v: char) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_str(self, _v: &str) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_none(self) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_unit())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> XmlResult<()> {
+        Err(not_unit())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> XmlResult<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_unit())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> XmlResult<Self::SerializeSeq> {
+        Err(not_unit())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> XmlResult<Self::SerializeTuple> {
+        Err(not_unit())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeTupleStruct> {
+        Err(not_unit())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeTupleVariant> {
+        Err(not_unit())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> XmlResult<Self::SerializeMap> {
+        Err(not_unit())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeStruct> {
+        Err(not_unit())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> XmlResult<Self::SerializeStructVariant> {
+        Err(not_unit())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xmlfmt::*;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Point {
+        x: i32,
+        y: u64,
+        label: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Line(i8, i8),
+        Rect { w: u16, h: u16 },
+    }
+
+    fn variant(name: &str, value: Value) -> Value {
+        Value::to_struct(vec![Member::new(name, value)])
+    }
+
+    #[test]
+    fn writes_pod_values() {
+        assert_eq!(to_value(&true).unwrap(), Value::Bool(true));
+        assert_eq!(to_value(&-12i8).unwrap(), Value::Int(-12));
+        assert_eq!(to_value(&40_000u16).unwrap(), Value::Int(40_000));
+        assert_eq!(to_value(&7i32).unwrap(), Value::Int(7));
+        assert_eq!(to_value(&7i64).unwrap(), Value::String("7".into()));
+        assert_eq!(to_value(&7u32).unwrap(), Value::String("7".into()));
+        assert_eq!(
+            to_value(&u64::MAX).unwrap(),
+            Value::String(u64::MAX.to_string())
+        );
+        assert_eq!(to_value(&1.5f32).unwrap(), Value::Double(1.5));
+        assert_eq!(to_value(&'c').unwrap(), Value::String("c".into()));
+        assert_eq!(to_value("text").unwrap(), Value::String("text".into()));
+    }
+
+    #[test]
+    fn writes_options_as_arrays() {
+        assert_eq!(to_value(&None::<i32>).unwrap(), Value::to_array(vec![]));
+        assert_eq!(
+            to_value(&Some(5)).unwrap(),
+            Value::to_array(vec![Value::Int(5)])
+        );
+    }
+
+    #[test]
+    fn writes_units_as_empty_structs() {
+        assert_eq!(to_value(&()).unwrap(), Value::to_struct(vec![]));
+    }
+
+    #[test]
+    fn writes_structs_in_field_order() {
+        let point = Point {
+            x: 1,
+            y: 2,
+            label: None,
+        };
+        let expected = Value::to_struct(vec![
+            Member::new("x", Value::Int(1)),
+            Member::new("y", Value::String("2".into())),
+            Member::new("label", Value::to_array(vec![])),
+        ]);
+        assert_eq!(to_value(&point).unwrap(), expected);
+    }
+
+    #[test]
+    fn writes_enum_variants_as_single_member_structs() {
+        assert_eq!(
+            to_value(&Shape::Empty).unwrap(),
+            variant("Empty", Value::to_struct(vec![]))
+        );
+        assert_eq!(
+            to_value(&Shape::Circle(2.0)).unwrap(),
+            variant("Circle", Value::Double(2.0))
+        );
+        assert_eq!(
+            to_value(&Shape::Line(1, -1)).unwrap(),
+            variant("Line", Value::to_array(vec![Value::Int(1), Value::Int(-1)]))
+        );
+        assert_eq!(
+            to_value(&Shape::Rect { w: 3, h: 4 }).unwrap(),
+            variant(
+                "Rect",
+                Value::to_struct(vec![
+                    Member::new("w", Value::Int(3)),
+                    Member::new("h", Value::Int(4)),
+                ])
+            )
+        );
+    }
+
+    #[test]
+    fn writes_maps_with_string_keys_only() {
+        let mut map = BTreeMap::new();
+        map.insert("a".to_owned(), 1);
+        assert_eq!(
+            to_value(&map).unwrap(),
+            Value::to_struct(vec![Member::new("a", Value::Int(1))])
+        );
+
+        let mut bad = BTreeMap::new();
+        bad.insert(vec![1], 1);
+        assert!(to_value(&bad).is_err());
+    }
+
+    #[test]
+    fn writes_tuples_as_positional_params() {
+        let params: Param = to_params(&(1, "x")).unwrap().into();
+        assert_eq!(params, vec![Value::Int(1), Value::String("x".into())]);
+
+        let params: Param = to_params(&42).unwrap().into();
+        assert_eq!(params, vec![Value::Int(42)]);
+    }
+}
@@ -1,9 +1,9 @@
 use crate::Params;
-use crate::xmlfmt::{Data, Member, Param};
+use crate::xmlfmt::writer::write_value;
+use crate::xmlfmt::{Data, Member, Param, ToXml, XmlDateTime, XmlResult};
 use serde::de::Unexpected;
 use serde::{Deserialize, Serialize};
 
-// TODO: Does serde_xml_rs handle box pointers? I'd like to run unit test on this one.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum Value {
@@ -21,9 +21,9 @@ pub enum Value {
     // Double precision floating point number.
     Double(f64),
 
-    #[deprecated = "XML-RPC forbids use of timezones, use another method instead."]
+    // Date and time without a timezone, as XML-RPC does not carry one.
     #[serde(rename = "dateTime.iso8601")]
-    DateTime(String),
+    DateTime(XmlDateTime),
 
     // Raw binary data of any length; encoded using Base64 on the wire.
     Base64(Vec<u8>),
@@ -38,6 +38,22 @@ pub enum Value {
 
     // translate this into <nil/>
     Nil,
+
+    // The types below are Apache/Python extensions, only read when extensions are enabled.
+    // Signed 64-bit integer, <i8>.
+    I8(i64),
+
+    // Signed 8-bit integer, <ex:i1>.
+    I1(i8),
+
+    // Signed 16-bit integer, <ex:i2>.
+    I2(i16),
+
+    // Single precision floating point number, <ex:float>.
+    Float(f32),
+
+    // Arbitrary precision decimal number kept as its text, <ex:bigdecimal>.
+    BigDecimal(String),
 }
 
 // This is considered as a "DataType"
@@ -50,12 +66,16 @@ impl Value {
             Value::Bool(v) => Unexpected::Bool(v),
             Value::String(ref v) => Unexpected::Str(v),
             Value::Double(v) => Unexpected::Float(v),
-            // This is fine, we've deprecated the usage of the method, but this library needs to handle it gracefully.
             Value::DateTime(_) => Unexpected::Other("dateTime.iso8601"),
             Value::Base64(ref v) => Unexpected::Bytes(v),
             Value::Array(_) => Unexpected::Seq,
             Value::Struct { .. } => Unexpected::Map,
             Value::Nil => Unexpected::Unit,
+            Value::I8(v) => Unexpected::Signed(v),
+            Value::I1(v) => Unexpected::Signed(i64::from(v)),
+            Value::I2(v) => Unexpected::Signed(i64::from(v)),
+            Value::Float(v) => Unexpected::Float(f64::from(v)),
+            Value::BigDecimal(_) => Unexpected::Other("bigdecimal"),
         }
     }
 
@@ -84,15 +104,24 @@ impl Value {
     }
 }
 
-impl Into<Param> for Value {
-    fn into(self) -> Param {
-        vec![self]
+// Writes the `<value>` fragment only, without any XML prolog.
+impl ToXml for Value {
+    fn to_xml(&self) -> XmlResult<String> {
+        let mut buf = String::new();
+        write_value(&mut buf, self)?;
+        Ok(buf)
     }
 }
 
-impl Into<Params> for Value {
-    fn into(self) -> Params {
-        Params::new(self.into())
+impl From<Value> for Param {
+    fn from(value: Value) -> Self {
+        vec![value]
+    }
+}
+
+impl From<Value> for Params {
+    fn from(value: Value) -> Self {
+        Params::new(value.into())
     }
 }
 
@@ -102,22 +131,70 @@ mod tests {
     use crate::xmlfmt::Params;
     use crate::xmlfmt::response::{MethodResponse, tests::*};
 
-    fn ser_and_de(value: Value) {
+    fn ser_and_de(value: Value, expected: &str) {
+        let data = value.to_xml();
+        assert!(
+            data.is_ok(),
+            "Unable to serialize value! {}",
+            data.unwrap_err()
+        );
+        assert_eq!(data.unwrap(), expected);
         let params = Params::new(vec![value]);
         ser_and_de_response_value(MethodResponse::Params(params));
     }
 
     #[test]
     fn writes_pod_xml_value() {
-        ser_and_de(Value::String("South Dakota".into()));
-        ser_and_de(Value::String("".into()));
-        ser_and_de(Value::Int(-33));
-        ser_and_de(Value::Bool(true));
-        ser_and_de(Value::Bool(false));
-        ser_and_de(Value::Double(-44.2));
-        // Value::DateTime will be phased out, so no unit test will be ran against this.
-        // ser_and_de(Value::DateTime("33".into()));
-        ser_and_de(Value::Base64("ASDF=".into()));
+        ser_and_de(
+            Value::String("South Dakota".into()),
+            "<value><string>South Dakota</string></value>",
+        );
+        ser_and_de(Value::String("".into()), "<value><string></string></value>");
+        ser_and_de(Value::Int(-33), "<value><int>-33</int></value>");
+        ser_and_de(Value::I4(-33), "<value><i4>-33</i4></value>");
+        ser_and_de(Value::Bool(true), "<value><boolean>1</boolean></value>");
+        ser_and_de(Value::Bool(false), "<value><boolean>0</boolean></value>");
+        ser_and_de(
+            Value::Double(-44.2),
+            "<value><double>-44.2</double></value>",
+        );
+        ser_and_de(
+            Value::DateTime(XmlDateTime::new(1998, 7, 17, 14, 8, 5).unwrap()),
+            "<value><dateTime.iso8601>19980717T14:08:05</dateTime.iso8601></value>",
+        );
+        ser_and_de(
+            Value::Base64("ASDF=".into()),
+            "<value><base64>QVNERj0=</base64></value>",
+        );
+        ser_and_de(
+            Value::Base64(vec![0, 159, 255]),
+            "<value><base64>AJ//</base64></value>",
+        );
+        ser_and_de(Value::Nil, "<value><nil/></value>");
+    }
+
+    #[test]
+    fn writes_extension_xml_value() {
+        ser_and_de(
+            Value::I8(-5_000_000_000),
+            r#"<value><ex:i8 xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">-5000000000</ex:i8></value>"#,
+        );
+        ser_and_de(
+            Value::I1(-7),
+            r#"<value><ex:i1 xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">-7</ex:i1></value>"#,
+        );
+        ser_and_de(
+            Value::I2(300),
+            r#"<value><ex:i2 xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">300</ex:i2></value>"#,
+        );
+        ser_and_de(
+            Value::Float(1.5),
+            r#"<value><ex:float xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">1.5</ex:float></value>"#,
+        );
+        ser_and_de(
+            Value::BigDecimal("-12.50".into()),
+            r#"<value><ex:bigdecimal xmlns:ex="http://ws.apache.org/xmlrpc/namespaces/extensions">-12.50</ex:bigdecimal></value>"#,
+        );
     }
 
     #[test]
@@ -129,7 +206,16 @@ mod tests {
         ];
         let params = Data::new(values);
         let array = Value::Array(Box::new(params));
-        ser_and_de(array);
+        ser_and_de(
+            array,
+            concat!(
+                "<value><array><data>",
+                "<value><int>33</int></value>",
+                "<value><int>-12</int></value>",
+                "<value><string>ASDF=</string></value>",
+                "</data></array></value>",
+            ),
+        );
     }
 
     #[test]
@@ -142,6 +228,14 @@ mod tests {
         let value = Value::Struct {
             member: Box::new(data),
         };
-        ser_and_de(value);
+        ser_and_de(
+            value,
+            concat!(
+                "<value><struct>",
+                "<member><name>foo</name><value><int>42</int></value></member>",
+                "<member><name>bar</name><value><string>baz</string></value></member>",
+                "</struct></value>",
+            ),
+        );
     }
 }
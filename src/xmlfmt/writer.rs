@@ -0,0 +1,216 @@
+use crate::xmlfmt::binary::encode_base64;
+use crate::xmlfmt::{Call, FmtError, MethodResponse, Params, Value, XmlError, XmlResult};
+use std::fmt::Write;
+
+/*
+    Hand-written encoder for the XML-RPC wire format, following http://xmlrpc.com/spec.md:
+    - every document starts with the `<?xml version="1.0"?>` prolog
+    - every argument is wrapped as `<param><value>...</value></param>` inside `<params>`
+    - every value is wrapped in `<value>`, with an explicit type element
+    Extension types use the Apache `ex:` names, with the namespace declared on the element itself,
+    so documents without them stay plain XML-RPC. Python reads them too, as it ignores the prefix.
+*/
+const PROLOG: &str = r#"<?xml version="1.0"?>"#;
+
+pub(crate) const EXTENSIONS_NAMESPACE: &str = "http://ws.apache.org/xmlrpc/namespaces/extensions";
+
+pub(crate) fn write_call(call: &Call) -> XmlResult<String> {
+    let mut buf = String::from(PROLOG);
+    buf.push_str("<methodCall><methodName>");
+    escape(&mut buf, &call.name)?;
+    buf.push_str("</methodName>");
+    write_params(&mut buf, &call.params)?;
+    buf.push_str("</methodCall>");
+    Ok(buf)
+}
+
+pub(crate) fn write_response(response: &MethodResponse) -> XmlResult<String> {
+    let mut buf = String::from(PROLOG);
+    buf.push_str("<methodResponse>");
+    match response {
+        MethodResponse::Params(params) => write_params(&mut buf, params)?,
+        MethodResponse::Fault(fault) => {
+            buf.push_str("<fault>");
+            write_value(&mut buf, &Value::from(fault.clone()))?;
+            buf.push_str("</fault>");
+        }
+    }
+    buf.push_str("</methodResponse>");
+    Ok(buf)
+}
+
+fn write_params(buf: &mut String, params: &Params) -> XmlResult<()> {
+    buf.push_str("<params>");
+    for value in params.values() {
+        buf.push_str("<param>");
+        write_value(buf, value)?;
+        buf.push_str("</param>");
+    }
+    buf.push_str("</params>");
+    Ok(())
+}
+
+pub(crate) fn write_value(buf: &mut String, value: &Value) -> XmlResult<()> {
+    buf.push_str("<value>");
+    match value {
+        Value::I4(v) => push_fmt(buf, format_args!("<i4>{}</i4>", v)),
+        Value::Int(v) => push_fmt(buf, format_args!("<int>{}</int>", v)),
+        Value::Bool(v) => push_fmt(buf, format_args!("<boolean>{}</boolean>", u8::from(*v))),
+        Value::String(v) => {
+            buf.push_str("<string>");
+            escape(buf, v)?;
+            buf.push_str("</string>");
+        }
+        Value::Double(v) => {
+            check_finite(v.is_finite(), v)?;
+            push_fmt(buf, format_args!("<double>{}</double>", v))
+        }
+        Value::DateTime(v) => push_fmt(
+            buf,
+            format_args!("<dateTime.iso8601>{}</dateTime.iso8601>", v),
+        ),
+        Value::Base64(v) => push_fmt(buf, format_args!("<base64>{}</base64>", encode_base64(v))),
+        Value::Array(data) => {
+            buf.push_str("<array><data>");
+            for value in data.values() {
+                write_value(buf, value)?;
+            }
+            buf.push_str("</data></array>");
+        }
+        Value::Struct { member } => {
+            buf.push_str("<struct>");
+            for member in member.iter() {
+                buf.push_str("<member><name>");
+                escape(buf, member.name())?;
+                buf.push_str("</name>");
+                write_value(buf, member.value())?;
+                buf.push_str("</member>");
+            }
+            buf.push_str("</struct>");
+        }
+        Value::Nil => buf.push_str("<nil/>"),
+        Value::I8(v) => push_extension(buf, "i8", v),
+        Value::I1(v) => push_extension(buf, "i1", v),
+        Value::I2(v) => push_extension(buf, "i2", v),
+        Value::Float(v) => {
+            check_finite(v.is_finite(), v)?;
+            push_extension(buf, "float", v)
+        }
+        Value::BigDecimal(v) => {
+            let mut text = String::new();
+            escape(&mut text, v)?;
+            push_extension(buf, "bigdecimal", text)
+        }
+    }
+    buf.push_str("</value>");
+    Ok(())
+}
+
+// The spec has no representation for infinities or NaN.
+fn check_finite<T: std::fmt::Display>(finite: bool, v: T) -> XmlResult<()> {
+    if finite {
+        return Ok(());
+    }
+    Err(XmlError::Format(FmtError::Encoding(format!(
+        "floating point number {} cannot be represented in XML-RPC",
+        v
+    ))))
+}
+
+fn push_extension<T: std::fmt::Display>(buf: &mut String, tag: &str, text: T) {
+    push_fmt(
+        buf,
+        format_args!(
+            r#"<ex:{tag} xmlns:ex="{}">{}</ex:{tag}>"#,
+            EXTENSIONS_NAMESPACE, text
+        ),
+    )
+}
+
+fn push_fmt(buf: &mut String, args: std::fmt::Arguments<'_>) {
+    // Writing into a String cannot fail.
+    let _ = buf.write_fmt(args);
+}
+
+// Only `<` and `&` are forbidden in character data, `>` is escaped for symmetry with other implementations.
+// A raw `\r` would be folded into `\n` by the reader, so it goes out as a character reference.
+// Most control characters cannot appear in an XML 1.0 document at all, not even as references.
+fn escape(buf: &mut String, text: &str) -> XmlResult<()> {
+    for c in text.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '\r' => buf.push_str("&#xD;"),
+            '\t' | '\n' => buf.push(c),
+            '\u{0}'..='\u{1F}' | '\u{FFFE}' | '\u{FFFF}' => {
+                return Err(XmlError::Format(FmtError::InvalidCharacter(format!(
+                    "character U+{:04X} cannot be represented in XML 1.0",
+                    u32::from(c)
+                ))));
+            }
+            c => buf.push(c),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xmlfmt::*;
+
+    #[test]
+    fn escapes_markup_in_text() {
+        let value = Value::String("<a> & b".into());
+        assert_eq!(
+            value.to_xml().unwrap(),
+            "<value><string>&lt;a&gt; &amp; b</string></value>"
+        );
+    }
+
+    #[test]
+    fn keeps_carriage_returns_across_round_trip() {
+        let value = Value::String("a\r\nb\rc\td".into());
+        let xml = value.to_xml().unwrap();
+        assert_eq!(xml, "<value><string>a&#xD;\nb&#xD;c\td</string></value>");
+        let call = Call::new("echo", Params::new(vec![value.clone()]));
+        let call = Call::from_xml(&call.to_xml().unwrap()).unwrap();
+        assert_eq!(call.params.values(), [value]);
+    }
+
+    #[test]
+    fn rejects_characters_outside_xml() {
+        for c in ['\u{0}', '\u{8}', '\u{B}', '\u{C}', '\u{1B}', '\u{1F}', '\u{FFFF}'] {
+            let text = format!("a{}b", c);
+            match Value::String(text.clone()).to_xml() {
+                Err(XmlError::Format(FmtError::InvalidCharacter(_))) => {}
+                other => panic!("{:?} should be rejected, got {:?}", c, other),
+            }
+            let member = Value::to_struct(vec![Member::new(text.clone(), Value::Nil)]);
+            assert!(member.to_xml().is_err());
+            let call = Call::new(text, Params::new(Vec::new()));
+            assert!(call.to_xml().is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_non_finite_doubles() {
+        assert!(Value::Double(f64::NAN).to_xml().is_err());
+        assert!(Value::Double(f64::INFINITY).to_xml().is_err());
+        assert!(Value::Float(f32::NEG_INFINITY).to_xml().is_err());
+    }
+
+    #[test]
+    fn writes_nested_values() {
+        let value = Value::to_struct(vec![Member::new(
+            "list",
+            Value::to_array(vec![Value::Nil, Value::to_struct(vec![])]),
+        )]);
+        assert_eq!(
+            value.to_xml().unwrap(),
+            "<value><struct><member><name>list</name><value><array><data>\
+             <value><nil/></value><value><struct></struct></value>\
+             </data></array></value></member></struct></value>"
+        );
+    }
+}